@@ -0,0 +1,258 @@
+use std::collections::HashMap;
+
+use steamy_vdf::{Entry, Table};
+
+use crate::steamid::SteamId;
+
+/// The typed contents of an app's `appmanifest_<appid>.acf` file.
+///
+/// Every value is optional since Steam omits keys that don't apply. Keys that this crate doesn't know about, or whose values couldn't be parsed, are kept in [`unknown`](#structfield.unknown) so no information is lost.
+///
+/// # Example
+/// ```rust,no_run
+/// # use steamlocate::SteamDir;
+/// let mut steamdir = SteamDir::locate().unwrap();
+/// let gmod = steamdir.app(&4000).unwrap();
+/// println!("{:?}", gmod.manifest.build_id);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+#[non_exhaustive]
+pub struct AppManifest {
+    /// `appid`: The app ID of the app.
+    pub app_id: Option<u32>,
+    /// `Universe`: The Steam universe the app belongs to, `1` being the public universe.
+    pub universe: Option<u32>,
+    /// `name`: The store name of the app.
+    pub name: Option<String>,
+    /// `StateFlags`: A bitfield describing the install state of the app.
+    pub state_flags: Option<u32>,
+    /// `installdir`: The name of the app's install directory within `steamapps/common`.
+    pub install_dir: Option<String>,
+    /// `LastUpdated`: The Unix timestamp of the last time the app was updated.
+    pub last_updated: Option<u64>,
+    /// `SizeOnDisk`: The size of the installed app in bytes.
+    pub size_on_disk: Option<u64>,
+    /// `StagingSize`: The size of the staged update in bytes.
+    pub staging_size: Option<u64>,
+    /// `buildid`: The ID of the installed build.
+    pub build_id: Option<u64>,
+    /// `LastOwner`: The SteamID of the last user that owned the app on this computer.
+    pub last_owner: Option<SteamId>,
+    /// `UpdateResult`: The result code of the last update, `0` being success.
+    pub update_result: Option<u64>,
+    /// `BytesToDownload`: The number of bytes that the current update needs to download.
+    pub bytes_to_download: Option<u64>,
+    /// `BytesDownloaded`: The number of bytes that the current update has downloaded so far.
+    pub bytes_downloaded: Option<u64>,
+    /// `BytesToStage`: The number of bytes that the current update needs to stage.
+    pub bytes_to_stage: Option<u64>,
+    /// `BytesStaged`: The number of bytes that the current update has staged so far.
+    pub bytes_staged: Option<u64>,
+    /// `TargetBuildID`: The ID of the build that the current update is installing.
+    pub target_build_id: Option<u64>,
+    /// `AutoUpdateBehavior`: `0` to keep the app updated, `1` to only update when launched and `2` for high priority updates.
+    pub auto_update_behavior: Option<u32>,
+    /// `AllowOtherDownloadsWhileRunning`: `0` to use the global setting, `1` to always allow and `2` to never allow.
+    pub allow_other_downloads_while_running: Option<u32>,
+    /// `ScheduledAutoUpdate`: The Unix timestamp of a scheduled update, or `0` if there is none.
+    pub scheduled_auto_update: Option<u64>,
+    /// `InstalledDepots`: The installed depots, keyed by depot ID.
+    pub installed_depots: HashMap<u32, Depot>,
+    /// `SharedDepots`: Depots that are shared with another app, mapping the depot ID to the app ID that installed it.
+    pub shared_depots: HashMap<u32, u32>,
+    /// `InstallScripts`: Install scripts that have been run, mapping the depot ID to the script's path.
+    pub install_scripts: HashMap<u32, String>,
+    /// `UserConfig`: Settings chosen by the user, such as `language` and `betakey`.
+    pub user_config: HashMap<String, String>,
+    /// `MountedConfig`: The settings that the installed files correspond to.
+    pub mounted_config: HashMap<String, String>,
+    /// Any other keys in the manifest.
+    pub unknown: HashMap<String, Entry>,
+}
+
+/// An installed depot listed in an [AppManifest](struct.AppManifest.html).
+#[derive(Clone, Debug, Default, PartialEq)]
+#[non_exhaustive]
+pub struct Depot {
+    /// `manifest`: The ID of the installed depot manifest.
+    pub manifest: Option<u64>,
+    /// `size`: The size of the depot in bytes.
+    pub size: Option<u64>,
+    /// `dlcappid`: The app ID of the DLC that this depot belongs to, if any.
+    pub dlc_app_id: Option<u32>,
+    /// Any other keys of the depot.
+    pub unknown: HashMap<String, Entry>,
+}
+
+impl AppManifest {
+    /// Builds the manifest from the contents of its `AppState` table
+    pub(crate) fn from_table(table: &Table) -> AppManifest {
+        let mut manifest = AppManifest::default();
+
+        for (key, entry) in table.iter() {
+            let parsed = match key.to_ascii_lowercase().as_str() {
+                "appid" => parse_into(entry, &mut manifest.app_id),
+                "universe" => parse_into(entry, &mut manifest.universe),
+                "name" => string_into(entry, &mut manifest.name),
+                "stateflags" => parse_into(entry, &mut manifest.state_flags),
+                "installdir" => string_into(entry, &mut manifest.install_dir),
+                "lastupdated" => parse_into(entry, &mut manifest.last_updated),
+                "sizeondisk" => parse_into(entry, &mut manifest.size_on_disk),
+                "stagingsize" => parse_into(entry, &mut manifest.staging_size),
+                "buildid" => parse_into(entry, &mut manifest.build_id),
+                "lastowner" => parse_into(entry, &mut manifest.last_owner),
+                "updateresult" => parse_into(entry, &mut manifest.update_result),
+                "bytestodownload" => parse_into(entry, &mut manifest.bytes_to_download),
+                "bytesdownloaded" => parse_into(entry, &mut manifest.bytes_downloaded),
+                "bytestostage" => parse_into(entry, &mut manifest.bytes_to_stage),
+                "bytesstaged" => parse_into(entry, &mut manifest.bytes_staged),
+                "targetbuildid" => parse_into(entry, &mut manifest.target_build_id),
+                "autoupdatebehavior" => parse_into(entry, &mut manifest.auto_update_behavior),
+                "allowotherdownloadswhilerunning" => {
+                    parse_into(entry, &mut manifest.allow_other_downloads_while_running)
+                }
+                "scheduledautoupdate" => parse_into(entry, &mut manifest.scheduled_auto_update),
+                "installeddepots" => map_into(entry, &mut manifest.installed_depots, |depot| {
+                    Some(Depot::from_table(depot.as_table()?))
+                }),
+                "shareddepots" => map_into(entry, &mut manifest.shared_depots, parse),
+                "installscripts" => map_into(entry, &mut manifest.install_scripts, string),
+                "userconfig" => map_into(entry, &mut manifest.user_config, string),
+                "mountedconfig" => map_into(entry, &mut manifest.mounted_config, string),
+                _ => false,
+            };
+
+            if !parsed {
+                manifest.unknown.insert(key.clone(), entry.clone());
+            }
+        }
+
+        manifest
+    }
+}
+
+impl Depot {
+    fn from_table(table: &Table) -> Depot {
+        let mut depot = Depot::default();
+
+        for (key, entry) in table.iter() {
+            let parsed = match key.to_ascii_lowercase().as_str() {
+                "manifest" => parse_into(entry, &mut depot.manifest),
+                "size" => parse_into(entry, &mut depot.size),
+                "dlcappid" => parse_into(entry, &mut depot.dlc_app_id),
+                _ => false,
+            };
+
+            if !parsed {
+                depot.unknown.insert(key.clone(), entry.clone());
+            }
+        }
+
+        depot
+    }
+}
+
+fn parse<T: std::str::FromStr>(entry: &Entry) -> Option<T> {
+    entry.as_value()?.parse().ok()
+}
+
+fn string(entry: &Entry) -> Option<String> {
+    entry.as_str().map(|str| str.to_string())
+}
+
+fn parse_into<T: std::str::FromStr>(entry: &Entry, field: &mut Option<T>) -> bool {
+    *field = parse(entry);
+    field.is_some()
+}
+
+fn string_into(entry: &Entry, field: &mut Option<String>) -> bool {
+    *field = string(entry);
+    field.is_some()
+}
+
+/// Fills `map` from a table entry, only succeeding if every key and value could be parsed
+fn map_into<K, V, F>(entry: &Entry, map: &mut HashMap<K, V>, value: F) -> bool
+where
+    K: std::str::FromStr + std::hash::Hash + Eq,
+    F: Fn(&Entry) -> Option<V>,
+{
+    let parsed: Option<HashMap<K, V>> = entry.as_table().and_then(|table| {
+        table
+            .iter()
+            .map(|(key, entry)| Some((key.parse().ok()?, value(entry)?)))
+            .collect()
+    });
+
+    match parsed {
+        Some(parsed) => {
+            *map = parsed;
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sanity() {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/sample_data/home/.steam/steam/steamapps/appmanifest_4000.acf"
+        );
+        let vdf = steamy_vdf::load(path).unwrap();
+        let manifest = AppManifest::from_table(vdf.get("AppState").unwrap().as_table().unwrap());
+
+        assert_eq!(manifest.app_id, Some(4000));
+        assert_eq!(manifest.universe, Some(1));
+        assert_eq!(manifest.name.as_deref(), Some("Garry's Mod"));
+        assert_eq!(manifest.state_flags, Some(4));
+        assert_eq!(manifest.install_dir.as_deref(), Some("GarrysMod"));
+        assert_eq!(manifest.size_on_disk, Some(4152333499));
+        assert_eq!(manifest.build_id, Some(12123796));
+        assert_eq!(
+            manifest.last_owner,
+            Some(SteamId::from_steam_id64(76561198040894045))
+        );
+        assert_eq!(manifest.auto_update_behavior, Some(0));
+        assert_eq!(
+            manifest.installed_depots.get(&4001),
+            Some(&Depot {
+                manifest: Some(8033896166589191357),
+                size: Some(3984374326),
+                dlc_app_id: None,
+                unknown: HashMap::new(),
+            })
+        );
+        let depot = manifest.installed_depots.get(&4003).unwrap();
+        assert_eq!(depot.dlc_app_id, Some(4010));
+        assert_eq!(depot.unknown.len(), 1);
+        assert_eq!(
+            depot.unknown.get("branch").and_then(|entry| entry.as_str()),
+            Some("beta")
+        );
+        assert_eq!(manifest.shared_depots.get(&228988), Some(&228980));
+        assert_eq!(
+            manifest.install_scripts.get(&4001).map(String::as_str),
+            Some("_CommonRedist\\vcredist\\2010\\installscript.vdf")
+        );
+        assert_eq!(
+            manifest.user_config.get("language").map(String::as_str),
+            Some("english")
+        );
+        assert_eq!(
+            manifest.mounted_config.get("language").map(String::as_str),
+            Some("english")
+        );
+        assert_eq!(manifest.unknown.len(), 1);
+        assert_eq!(
+            manifest
+                .unknown
+                .get("LauncherPath")
+                .and_then(|entry| entry.as_str()),
+            Some("/home/user/.local/share/Steam/ubuntu12_32/steam")
+        );
+    }
+}
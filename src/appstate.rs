@@ -0,0 +1,33 @@
+bitflags::bitflags! {
+    /// The install state of a Steam app, decoded from the `StateFlags` key of its manifest.
+    ///
+    /// Several flags are usually set at once, for example an app that is downloading an update is `FULLY_INSTALLED | UPDATE_REQUIRED | UPDATE_RUNNING | DOWNLOADING`. Bits that aren't listed here are kept as they are.
+    ///
+    /// See [SteamApp::state](struct.SteamApp.html#structfield.state) and its convenience methods.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    pub struct AppState: u32 {
+        const UNINSTALLED = 1 << 0;
+        const UPDATE_REQUIRED = 1 << 1;
+        const FULLY_INSTALLED = 1 << 2;
+        const ENCRYPTED = 1 << 3;
+        const LOCKED = 1 << 4;
+        const FILES_MISSING = 1 << 5;
+        const APP_RUNNING = 1 << 6;
+        const FILES_CORRUPT = 1 << 7;
+        const UPDATE_RUNNING = 1 << 8;
+        const UPDATE_PAUSED = 1 << 9;
+        const UPDATE_STARTED = 1 << 10;
+        const UNINSTALLING = 1 << 11;
+        const BACKUP_RUNNING = 1 << 12;
+        const RECONFIGURING = 1 << 16;
+        const VALIDATING = 1 << 17;
+        const ADDING_FILES = 1 << 18;
+        const PREALLOCATING = 1 << 19;
+        const DOWNLOADING = 1 << 20;
+        const STAGING = 1 << 21;
+        const COMMITTING = 1 << 22;
+        const UPDATE_STOPPING = 1 << 23;
+
+        const _ = !0;
+    }
+}
@@ -0,0 +1,191 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::error::{Error, Result};
+
+/// The kinds of custom artwork that Steam shows for a game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ArtworkKind {
+    /// `<appid>p`: The portrait capsule shown in the library grid.
+    Portrait,
+    /// `<appid>`: The landscape capsule shown for recently played games and in Big Picture.
+    Landscape,
+    /// `<appid>_hero`: The wide banner at the top of the game's library page.
+    Hero,
+    /// `<appid>_logo`: The logo drawn over the hero.
+    Logo,
+    /// `<appid>_icon`: The icon shown in the library list.
+    Icon,
+}
+
+impl ArtworkKind {
+    /// Every kind of artwork.
+    pub const ALL: [ArtworkKind; 5] = [
+        ArtworkKind::Portrait,
+        ArtworkKind::Landscape,
+        ArtworkKind::Hero,
+        ArtworkKind::Logo,
+        ArtworkKind::Icon,
+    ];
+
+    /// The suffix that follows the app ID in the artwork's file name.
+    pub fn suffix(self) -> &'static str {
+        match self {
+            ArtworkKind::Portrait => "p",
+            ArtworkKind::Landscape => "",
+            ArtworkKind::Hero => "_hero",
+            ArtworkKind::Logo => "_logo",
+            ArtworkKind::Icon => "_icon",
+        }
+    }
+}
+
+/// The custom artwork of a non-Steam game, which is kept in `userdata/<account_id>/config/grid`.
+///
+/// See [Shortcut::artwork](struct.Shortcut.html#method.artwork).
+///
+/// # Example
+/// ```rust,no_run
+/// # use std::path::Path;
+/// # use steamlocate::{ArtworkKind, SteamDir};
+/// let mut steamdir = SteamDir::locate().unwrap();
+/// let path = steamdir.path.clone();
+/// let shortcut = &steamdir.shortcuts()[0];
+/// let artwork = shortcut.artwork(&path).unwrap();
+/// if artwork.existing().is_empty() {
+///     artwork
+///         .install(ArtworkKind::Portrait, Path::new("/tmp/cover.png"))
+///         .unwrap();
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Artwork {
+    /// The user's `grid` folder, which may not exist yet.
+    pub grid_dir: PathBuf,
+    /// The app ID that the artwork's file names start with.
+    pub appid: u32,
+}
+
+/// The extensions that Steam loads artwork from, in order of preference.
+const EXTENSIONS: [&str; 2] = ["png", "jpg"];
+
+impl Artwork {
+    /// The path that artwork of the given kind is stored at when it has the given extension, such as `png`.
+    pub fn path(&self, kind: ArtworkKind, extension: &str) -> PathBuf {
+        self.grid_dir
+            .join(format!("{}{}.{}", self.appid, kind.suffix(), extension))
+    }
+
+    /// Returns the artwork that is installed, along with the path to each file.
+    pub fn existing(&self) -> Vec<(ArtworkKind, PathBuf)> {
+        ArtworkKind::ALL
+            .iter()
+            .filter_map(|kind| {
+                EXTENSIONS
+                    .iter()
+                    .map(|extension| self.path(*kind, extension))
+                    .find(|path| path.is_file())
+                    .map(|path| (*kind, path))
+            })
+            .collect()
+    }
+
+    /// Copies `image` into the `grid` folder as artwork of the given kind, returning the path that it was copied to.
+    ///
+    /// The image keeps its extension, which must be `png` or `jpg` (`jpeg` is renamed to `jpg`), and an [`Error::UnsupportedLayout`](enum.Error.html#variant.UnsupportedLayout) is returned for anything else, including an image without an extension. Any artwork of the same kind that has a different extension is removed so that Steam doesn't keep showing it.
+    pub fn install(&self, kind: ArtworkKind, image: &Path) -> Result<PathBuf> {
+        let extension = image
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_ascii_lowercase())
+            .map(|extension| match extension.as_str() {
+                "jpeg" => "jpg".to_string(),
+                _ => extension,
+            })
+            .unwrap_or_default();
+        if !EXTENSIONS.contains(&extension.as_str()) {
+            return Err(Error::unsupported_layout(
+                image,
+                format!(
+                    "Steam only loads artwork with the extensions {:?}",
+                    EXTENSIONS
+                ),
+            ));
+        }
+
+        fs::create_dir_all(&self.grid_dir).map_err(|err| Error::io(err, &self.grid_dir))?;
+        let destination = self.path(kind, &extension);
+        fs::copy(image, &destination).map_err(|err| Error::io(err, image))?;
+
+        for other in EXTENSIONS.iter().filter(|other| **other != extension) {
+            let stale = self.path(kind, other);
+            if stale.is_file() {
+                fs::remove_file(&stale).map_err(|err| Error::io(err, &stale))?;
+            }
+        }
+
+        Ok(destination)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::TempDir;
+
+    #[test]
+    fn install_and_find() {
+        let grid_dir = TempDir::new("artwork");
+        let artwork = Artwork {
+            grid_dir: grid_dir.join("grid"),
+            appid: 2786274309,
+        };
+        assert_eq!(
+            artwork.path(ArtworkKind::Portrait, "png"),
+            grid_dir.join("grid/2786274309p.png")
+        );
+        assert!(artwork.existing().is_empty());
+
+        fs::create_dir_all(&grid_dir).unwrap();
+        let png = grid_dir.join("cover.png");
+        let jpeg = grid_dir.join("cover.JPEG");
+        fs::write(&png, b"png").unwrap();
+        fs::write(&jpeg, b"jpg").unwrap();
+
+        let installed = artwork.install(ArtworkKind::Portrait, &png).unwrap();
+        assert_eq!(installed, artwork.path(ArtworkKind::Portrait, "png"));
+        artwork.install(ArtworkKind::Hero, &png).unwrap();
+        // Replacing artwork with a different format removes the old file
+        let installed = artwork.install(ArtworkKind::Portrait, &jpeg).unwrap();
+        assert_eq!(installed, grid_dir.join("grid/2786274309p.jpg"));
+
+        // Steam would never load a `webp`, so it isn't installed
+        let webp = grid_dir.join("cover.webp");
+        fs::write(&webp, b"webp").unwrap();
+        assert!(matches!(
+            artwork.install(ArtworkKind::Logo, &webp),
+            Err(Error::UnsupportedLayout { .. })
+        ));
+        assert!(!artwork.path(ArtworkKind::Logo, "webp").exists());
+        // Nor is an image of an unknown format
+        let unknown = grid_dir.join("cover");
+        fs::write(&unknown, b"png").unwrap();
+        assert!(matches!(
+            artwork.install(ArtworkKind::Logo, &unknown),
+            Err(Error::UnsupportedLayout { .. })
+        ));
+        assert!(!artwork.path(ArtworkKind::Logo, "png").exists());
+
+        assert_eq!(
+            artwork.existing(),
+            vec![
+                (ArtworkKind::Portrait, installed),
+                (ArtworkKind::Hero, artwork.path(ArtworkKind::Hero, "png")),
+            ]
+        );
+    }
+}
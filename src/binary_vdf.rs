@@ -0,0 +1,484 @@
+//! A parser for the binary KeyValues format, which Steam uses for files like `shortcuts.vdf`.
+//!
+//! A binary VDF file is a map of keys to values. Each entry starts with a byte for its type, followed by its null-terminated key and then its value, and a map is ended by `0x08`. The types are
+//!
+//! | Byte   | Type                                              |
+//! |--------|---------------------------------------------------|
+//! | `0x00` | A nested map                                      |
+//! | `0x01` | A null-terminated UTF-8 string                    |
+//! | `0x02` | A little-endian `i32`                             |
+//! | `0x03` | A little-endian `f32`                             |
+//! | `0x04` | A little-endian pointer, stored as an `i32`       |
+//! | `0x05` | A null-terminated little-endian UTF-16 string     |
+//! | `0x06` | A color, stored as 4 RGBA bytes                   |
+//! | `0x07` | A little-endian `u64`                             |
+//! | `0x0A` | A little-endian `i64`                             |
+//!
+//! `0x0B` is also accepted as the end of a map.
+//!
+//! # Example
+//! ```rust,no_run
+//! # use steamlocate::{binary_vdf, SteamDir};
+//! let steamdir = SteamDir::locate().unwrap();
+//! let path = steamdir.path.join("userdata/12345678/config/shortcuts.vdf");
+//! let vdf = binary_vdf::load(&path).unwrap();
+//! let shortcuts = vdf.get("shortcuts").and_then(|value| value.as_map()).unwrap();
+//! for (index, shortcut) in shortcuts.iter() {
+//!     println!("{}: {:?}", index, shortcut.as_map().and_then(|map| map.get("AppName")));
+//! }
+//! ```
+
+use std::{fmt, fs, path::Path};
+
+use crate::error::{Error, Result};
+
+const MAP: u8 = 0x00;
+const STRING: u8 = 0x01;
+const INT32: u8 = 0x02;
+const FLOAT: u8 = 0x03;
+const POINTER: u8 = 0x04;
+const WIDE_STRING: u8 = 0x05;
+const COLOR: u8 = 0x06;
+const UINT64: u8 = 0x07;
+const END: u8 = 0x08;
+const INT64: u8 = 0x0A;
+const ALTERNATE_END: u8 = 0x0B;
+
+/// How deeply maps may be nested, which keeps a corrupt file from overflowing the stack
+const MAX_DEPTH: usize = 64;
+
+/// A value in a binary VDF file.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    /// `0x00`: A nested map.
+    Map(Map),
+    /// `0x01`: A string.
+    String(String),
+    /// `0x02`: A 32-bit signed integer.
+    Int32(i32),
+    /// `0x03`: A 32-bit float.
+    Float(f32),
+    /// `0x04`: A pointer, which is only meaningful to the process that wrote the file.
+    Pointer(i32),
+    /// `0x05`: A string that was stored as UTF-16.
+    WideString(String),
+    /// `0x06`: A color as its red, green, blue and alpha bytes.
+    Color([u8; 4]),
+    /// `0x07`: A 64-bit unsigned integer.
+    UInt64(u64),
+    /// `0x0A`: A 64-bit signed integer.
+    Int64(i64),
+}
+
+impl Value {
+    /// Returns the nested map, if this is one.
+    pub fn as_map(&self) -> Option<&Map> {
+        match self {
+            Value::Map(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    /// Returns the string, if this is a string or a wide string.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(string) | Value::WideString(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    /// Returns the integer, if this is a 32-bit integer.
+    pub fn as_i32(&self) -> Option<i32> {
+        match self {
+            Value::Int32(int) => Some(*int),
+            _ => None,
+        }
+    }
+
+    /// Returns the float, if this is one.
+    pub fn as_f32(&self) -> Option<f32> {
+        match self {
+            Value::Float(float) => Some(*float),
+            _ => None,
+        }
+    }
+
+    /// Returns the integer, if this is a 64-bit unsigned integer.
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            Value::UInt64(int) => Some(*int),
+            _ => None,
+        }
+    }
+
+    /// Returns the integer, if this is a 64-bit signed integer.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Value::Int64(int) => Some(*int),
+            _ => None,
+        }
+    }
+}
+
+/// A map of keys to values, in the order that they appear in the file.
+///
+/// Steam isn't consistent with the casing of keys, so lookups ignore ASCII case.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Map {
+    entries: Vec<(String, Value)>,
+}
+
+impl Map {
+    /// Creates an empty map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the value of the first entry whose key matches `key`, ignoring ASCII case.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.entries
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, value)| value)
+    }
+
+    /// Returns a mutable reference to the value of the first entry whose key matches `key`, ignoring ASCII case.
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Value> {
+        self.entries
+            .iter_mut()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, value)| value)
+    }
+
+    /// Sets the value of the first entry whose key matches `key`, ignoring ASCII case, returning its previous value.
+    ///
+    /// The entry keeps its position and the casing of its key. If there is no such entry, a new one is added at the end.
+    pub fn insert(&mut self, key: impl Into<String>, value: Value) -> Option<Value> {
+        let key = key.into();
+        match self.get_mut(&key) {
+            Some(existing) => Some(std::mem::replace(existing, value)),
+            None => {
+                self.entries.push((key, value));
+                None
+            }
+        }
+    }
+
+    /// Removes the first entry whose key matches `key`, ignoring ASCII case, returning its value.
+    pub fn remove(&mut self, key: &str) -> Option<Value> {
+        let index = self
+            .entries
+            .iter()
+            .position(|(k, _)| k.eq_ignore_ascii_case(key))?;
+        Some(self.entries.remove(index).1)
+    }
+
+    /// Iterates over the entries in order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Value)> + '_ {
+        self.entries
+            .iter()
+            .map(|(key, value)| (key.as_str(), value))
+    }
+
+    /// The number of entries in the map.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the map has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// An error encountered while parsing a binary VDF file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ParseError {
+    /// The 0-based byte offset where the error occurred.
+    pub offset: usize,
+    /// A description of the error.
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at byte offset {}", self.message, self.offset)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parses the contents of a binary VDF file.
+///
+/// The outermost map may be ended either by `0x08` or by the end of `contents`.
+pub fn parse(contents: &[u8]) -> std::result::Result<Map, ParseError> {
+    let mut parser = Parser {
+        contents,
+        offset: 0,
+    };
+    parser.map(0)
+}
+
+/// Serializes `map` in the binary format, which results in the same bytes that it was parsed from.
+///
+/// Ending maps with `0x0B` isn't preserved, all of them are ended with `0x08`.
+pub fn to_bytes(map: &Map) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    write_map(map, &mut bytes);
+    bytes
+}
+
+/// Reads and parses the binary VDF file at `path`.
+pub fn load(path: &Path) -> Result<Map> {
+    let contents = fs::read(path).map_err(|err| Error::io(err, path))?;
+    parse(&contents).map_err(|err| Error::binary_vdf(err, path))
+}
+
+fn write_map(map: &Map, bytes: &mut Vec<u8>) {
+    for (key, value) in &map.entries {
+        let value_type = match value {
+            Value::Map(_) => MAP,
+            Value::String(_) => STRING,
+            Value::Int32(_) => INT32,
+            Value::Float(_) => FLOAT,
+            Value::Pointer(_) => POINTER,
+            Value::WideString(_) => WIDE_STRING,
+            Value::Color(_) => COLOR,
+            Value::UInt64(_) => UINT64,
+            Value::Int64(_) => INT64,
+        };
+        bytes.push(value_type);
+        write_string(key, bytes);
+
+        match value {
+            Value::Map(map) => write_map(map, bytes),
+            Value::String(string) => write_string(string, bytes),
+            Value::Int32(int) | Value::Pointer(int) => bytes.extend_from_slice(&int.to_le_bytes()),
+            Value::Float(float) => bytes.extend_from_slice(&float.to_le_bytes()),
+            Value::WideString(string) => {
+                for unit in string.encode_utf16().chain(std::iter::once(0)) {
+                    bytes.extend_from_slice(&unit.to_le_bytes());
+                }
+            }
+            Value::Color(color) => bytes.extend_from_slice(color),
+            Value::UInt64(int) => bytes.extend_from_slice(&int.to_le_bytes()),
+            Value::Int64(int) => bytes.extend_from_slice(&int.to_le_bytes()),
+        }
+    }
+    bytes.push(END);
+}
+
+fn write_string(string: &str, bytes: &mut Vec<u8>) {
+    bytes.extend_from_slice(string.as_bytes());
+    bytes.push(0x00);
+}
+
+struct Parser<'a> {
+    contents: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(offset: usize, message: impl Into<String>) -> ParseError {
+        ParseError {
+            offset,
+            message: message.into(),
+        }
+    }
+
+    fn map(&mut self, depth: usize) -> std::result::Result<Map, ParseError> {
+        let mut map = Map::new();
+
+        loop {
+            let type_offset = self.offset;
+            let value_type = match self.contents.get(type_offset) {
+                Some(value_type) => *value_type,
+                None if depth == 0 => return Ok(map),
+                None => return Err(Self::error(type_offset, "unexpected end of input in map")),
+            };
+            self.offset += 1;
+
+            if value_type == END || value_type == ALTERNATE_END {
+                return Ok(map);
+            }
+
+            let key = self.string()?;
+            let value = match value_type {
+                MAP if depth == MAX_DEPTH => {
+                    return Err(Self::error(type_offset, "nesting too deep"))
+                }
+                MAP => Value::Map(self.map(depth + 1)?),
+                STRING => Value::String(self.string()?),
+                INT32 => Value::Int32(i32::from_le_bytes(self.take()?)),
+                FLOAT => Value::Float(f32::from_le_bytes(self.take()?)),
+                POINTER => Value::Pointer(i32::from_le_bytes(self.take()?)),
+                WIDE_STRING => Value::WideString(self.wide_string()?),
+                COLOR => Value::Color(self.take()?),
+                UINT64 => Value::UInt64(u64::from_le_bytes(self.take()?)),
+                INT64 => Value::Int64(i64::from_le_bytes(self.take()?)),
+                unknown => {
+                    return Err(Self::error(
+                        type_offset,
+                        format!("unknown value type 0x{:02x}", unknown),
+                    ))
+                }
+            };
+            map.entries.push((key, value));
+        }
+    }
+
+    fn take<const N: usize>(&mut self) -> std::result::Result<[u8; N], ParseError> {
+        let bytes = self
+            .contents
+            .get(self.offset..self.offset + N)
+            .ok_or_else(|| Self::error(self.offset, "unexpected end of input in value"))?;
+        self.offset += N;
+
+        let mut array = [0; N];
+        array.copy_from_slice(bytes);
+        Ok(array)
+    }
+
+    fn string(&mut self) -> std::result::Result<String, ParseError> {
+        let start = self.offset;
+        let rest = &self.contents[start..];
+        let len = rest
+            .iter()
+            .position(|b| *b == 0x00)
+            .ok_or_else(|| Self::error(start, "unterminated string"))?;
+        self.offset += len + 1;
+
+        Ok(String::from_utf8_lossy(&rest[..len]).into_owned())
+    }
+
+    fn wide_string(&mut self) -> std::result::Result<String, ParseError> {
+        let start = self.offset;
+        let mut units = Vec::new();
+        loop {
+            let unit = u16::from_le_bytes(
+                self.take()
+                    .map_err(|_| Self::error(start, "unterminated wide string"))?,
+            );
+            if unit == 0 {
+                break;
+            }
+            units.push(unit);
+        }
+
+        Ok(String::from_utf16_lossy(&units))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sanity() {
+        let contents = include_bytes!("../tests/sample_data/shortcuts.vdf");
+        let vdf = parse(contents).unwrap();
+
+        assert_eq!(vdf.len(), 1);
+        let shortcuts = vdf.get("shortcuts").and_then(Value::as_map).unwrap();
+        let keys: Vec<_> = shortcuts.iter().map(|(key, _)| key).collect();
+        assert_eq!(keys, vec!["0", "1", "2"]);
+
+        let anki = shortcuts.get("0").and_then(Value::as_map).unwrap();
+        assert_eq!(anki.get("appid"), Some(&Value::Int32(-1508692987)));
+        // Keys are matched regardless of case
+        assert_eq!(anki.get("appname").and_then(Value::as_str), Some("Anki"));
+        assert_eq!(anki.get("LaunchOptions").and_then(Value::as_str), Some(""));
+        assert_eq!(anki.get("AllowOverlay").and_then(Value::as_i32), Some(1));
+        assert_eq!(anki.get("tags"), Some(&Value::Map(Map::new())));
+    }
+
+    #[test]
+    fn all_value_types() {
+        let mut contents = Vec::new();
+        contents.extend_from_slice(b"\x00root\x00");
+        contents.extend_from_slice(b"\x01string\x00value\x00");
+        contents.extend_from_slice(b"\x02int32\x00");
+        contents.extend_from_slice(&(-2i32).to_le_bytes());
+        contents.extend_from_slice(b"\x03float\x00");
+        contents.extend_from_slice(&1.5f32.to_le_bytes());
+        contents.extend_from_slice(b"\x04pointer\x00");
+        contents.extend_from_slice(&3i32.to_le_bytes());
+        contents.extend_from_slice(b"\x05wide\x00h\x00\xe9\x00\x00\x00");
+        contents.extend_from_slice(b"\x06color\x00\x01\x02\x03\x04");
+        contents.extend_from_slice(b"\x07uint64\x00");
+        contents.extend_from_slice(&u64::MAX.to_le_bytes());
+        contents.extend_from_slice(b"\x0aint64\x00");
+        contents.extend_from_slice(&(-4i64).to_le_bytes());
+        contents.extend_from_slice(b"\x0b\x08");
+
+        let vdf = parse(&contents).unwrap();
+        let mut written = to_bytes(&vdf);
+        // `0x0B` is written as `0x08`
+        let len = written.len();
+        written[len - 2] = ALTERNATE_END;
+        assert_eq!(written, contents);
+        let root = vdf.get("root").and_then(Value::as_map).unwrap();
+        let values: Vec<_> = root.iter().map(|(_, value)| value.clone()).collect();
+        assert_eq!(
+            values,
+            vec![
+                Value::String("value".into()),
+                Value::Int32(-2),
+                Value::Float(1.5),
+                Value::Pointer(3),
+                Value::WideString("hé".into()),
+                Value::Color([1, 2, 3, 4]),
+                Value::UInt64(u64::MAX),
+                Value::Int64(-4),
+            ]
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let contents = include_bytes!("../tests/sample_data/shortcuts.vdf");
+        assert_eq!(to_bytes(&parse(contents).unwrap()), contents);
+        let contents = include_bytes!("../tests/sample_data/shortcuts_different_key_case.vdf");
+        assert_eq!(to_bytes(&parse(contents).unwrap()), contents);
+    }
+
+    #[test]
+    fn insert_and_remove() {
+        let mut map = Map::new();
+        assert_eq!(map.insert("AppName", Value::String("Anki".into())), None);
+        assert_eq!(map.insert("Exe", Value::String("anki".into())), None);
+        assert_eq!(
+            map.insert("appname", Value::String("Calc".into())),
+            Some(Value::String("Anki".into()))
+        );
+        let keys: Vec<_> = map.iter().map(|(key, _)| key).collect();
+        assert_eq!(keys, vec!["AppName", "Exe"]);
+
+        assert_eq!(map.remove("EXE"), Some(Value::String("anki".into())));
+        assert_eq!(map.remove("Exe"), None);
+        assert_eq!(map.len(), 1);
+    }
+
+    #[test]
+    fn errors_have_offsets() {
+        let contents = include_bytes!("../tests/sample_data/shortcuts.vdf");
+        let err = parse(&contents[..100]).unwrap_err();
+        assert_eq!(err.offset, 87);
+
+        let err = parse(b"\x00root\x00\x09key\x00").unwrap_err();
+        assert_eq!(err.offset, 6);
+        assert_eq!(err.message, "unknown value type 0x09");
+
+        let err = parse(b"\x02int\x00\x01\x02").unwrap_err();
+        assert_eq!(err.offset, 5);
+
+        // Deeply nested maps are rejected instead of overflowing the stack
+        let err = parse(&b"\x00a\x00".repeat(2_000_000)).unwrap_err();
+        assert_eq!(err.offset, 3 * MAX_DEPTH);
+        assert_eq!(err.message, "nesting too deep");
+        let mut nested = b"\x00a\x00".repeat(MAX_DEPTH);
+        nested.extend(vec![END; MAX_DEPTH]);
+        assert!(parse(&nested).is_ok());
+    }
+}
@@ -0,0 +1,75 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::libraryfolders::LibraryFolders;
+use crate::protonprefix::ProtonPrefix;
+
+/// The Proton prefix of an app, kept in `steamapps/compatdata/<appid>` within a library folder.
+///
+/// Steam creates it the first time the app is run through Proton, and Proton sets up [`pfx`](#structfield.pfx) as the Wine prefix that the app sees as its Windows installation.
+///
+/// # Example
+/// ```rust,no_run
+/// # use steamlocate::SteamDir;
+/// let mut steamdir = SteamDir::locate().unwrap();
+/// if let Some(compat_data) = steamdir.compat_data(4000) {
+///     println!("{} ({:?})", compat_data.pfx.display(), compat_data.config_info);
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CompatData {
+    /// The `compatdata/<appid>` folder.
+    ///
+    /// Example: `/home/alice/.local/share/Steam/steamapps/compatdata/4000`
+    pub path: PathBuf,
+    /// The Wine prefix within the folder, which may not exist yet if the app never ran.
+    pub pfx: PathBuf,
+    /// `version`: The version of the prefix's layout, which Proton upgrades when a newer Proton runs the app, e.g. `8.0-103`.
+    pub version: Option<String>,
+    /// The first line of `config_info`: The build of Proton that last ran the app, e.g. `1695315413 proton-8.0-4`.
+    pub config_info: Option<String>,
+}
+
+impl CompatData {
+    /// Reads the prefix in the `compatdata/<appid>` folder at `path`, returning `None` if the folder doesn't exist
+    pub(crate) fn new(path: PathBuf) -> Option<Self> {
+        if !path.is_dir() {
+            return None;
+        }
+
+        let first_line = |file_name| {
+            let contents = fs::read_to_string(path.join(file_name)).ok()?;
+            let line = contents.lines().next()?.trim();
+            Some(line.to_string()).filter(|line| !line.is_empty())
+        };
+
+        Some(CompatData {
+            pfx: path.join("pfx"),
+            version: first_line("version"),
+            config_info: first_line("config_info"),
+            path,
+        })
+    }
+
+    /// The Wine prefix in [`pfx`](#structfield.pfx).
+    pub fn prefix(&self) -> ProtonPrefix {
+        ProtonPrefix::new(self.pfx.clone())
+    }
+}
+
+/// Finds the prefix of `app_id`, looking in the `steamapps` folder of `preferred` before every library folder in order
+pub(crate) fn find(
+    app_id: u32,
+    preferred: Option<&Path>,
+    libraryfolders: &LibraryFolders,
+) -> Option<CompatData> {
+    preferred
+        .into_iter()
+        .chain(libraryfolders.paths.iter().map(PathBuf::as_path))
+        .find_map(|steamapps| {
+            CompatData::new(steamapps.join("compatdata").join(app_id.to_string()))
+        })
+}
@@ -0,0 +1,107 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use keyvalues_parser::{Obj, Vdf};
+
+use crate::error::{Error, Result};
+use crate::keyvalues;
+
+/// The compatibility tools, such as Proton, that apps are set to run with, read from `CompatToolMapping` in `config/config.vdf`.
+///
+/// Only apps that were given a tool in their properties are listed by their own ID. The rest use the `0` entry, see [`default`](#structfield.default).
+///
+/// # Example
+/// ```rust,no_run
+/// # use steamlocate::SteamDir;
+/// let mut steamdir = SteamDir::locate().unwrap();
+/// let mapping = steamdir.compat_tool_mapping().unwrap();
+/// let app = steamdir.app(&4000).unwrap();
+/// if let Some(tool) = app.compat_tool(&mapping) {
+///     println!("{} runs with {}", app.appid, tool.name);
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CompatToolMapping {
+    /// The path to the `config.vdf` file.
+    pub path: PathBuf,
+    /// The tool that each app is set to run with, keyed by app ID.
+    ///
+    /// This includes non-Steam games, which are keyed by their shortcut's app ID. Apps whose tool was cleared (an empty `name`) aren't included.
+    pub apps: HashMap<u32, AppCompatTool>,
+    /// `0`: The tool that Steam Play uses for every other app, if it is enabled for all titles.
+    pub default: Option<AppCompatTool>,
+}
+
+/// The compatibility tool that an app is set to run with, see [CompatToolMapping](struct.CompatToolMapping.html).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct AppCompatTool {
+    /// `name`: The internal name of the tool, such as `proton_experimental` or `GE-Proton8-25`.
+    pub name: String,
+    /// `config`: Extra options for the tool, which is usually empty.
+    pub config: String,
+    /// `priority`: The priority of the mapping, which Steam uses to decide between the user's choice and its own defaults.
+    pub priority: Option<u32>,
+}
+
+impl CompatToolMapping {
+    /// Reads the `CompatToolMapping` from the `config.vdf` file at `path`.
+    pub fn load(path: &Path) -> Result<Self> {
+        let vdf_text = keyvalues::read(path)?;
+        let value = Vdf::parse(&vdf_text)
+            .map_err(|err| Error::keyvalues(err, path))?
+            .value;
+        let obj = value
+            .get_obj()
+            .ok_or_else(|| Error::unsupported_layout(path, "expected an object"))?;
+
+        let mut mapping = CompatToolMapping {
+            path: path.to_owned(),
+            ..Default::default()
+        };
+        let tools =
+            keyvalues::get_obj_path(obj, &["Software", "Valve", "Steam", "CompatToolMapping"]);
+        for (app_id, values) in tools.into_iter().flatten() {
+            let tool = match values.first().and_then(|value| value.get_obj()) {
+                Some(tool) => AppCompatTool::from_obj(tool),
+                None => continue,
+            };
+            match app_id.parse() {
+                _ if tool.name.is_empty() => {}
+                Ok(0) => mapping.default = Some(tool),
+                Ok(app_id) => {
+                    mapping.apps.insert(app_id, tool);
+                }
+                Err(_) => {}
+            }
+        }
+
+        Ok(mapping)
+    }
+
+    /// Returns the tool that the app with the given ID is set to run with, falling back to the [`default`](#structfield.default).
+    ///
+    /// Steam only uses the default for apps that don't run natively, which this can't tell apart.
+    pub fn get(&self, app_id: u32) -> Option<&AppCompatTool> {
+        self.apps.get(&app_id).or(self.default.as_ref())
+    }
+}
+
+impl AppCompatTool {
+    fn from_obj(obj: &Obj) -> Self {
+        let string = |key| {
+            keyvalues::get_str(obj, key)
+                .map(|value| value.to_string())
+                .unwrap_or_default()
+        };
+
+        AppCompatTool {
+            name: string("name"),
+            config: string("config"),
+            priority: keyvalues::get_str(obj, "priority").and_then(|value| value.parse().ok()),
+        }
+    }
+}
@@ -0,0 +1,287 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use keyvalues_parser::{Obj, Vdf};
+
+use crate::compatmapping::CompatToolMapping;
+use crate::error::{Error, Result};
+use crate::keyvalues;
+use crate::steamapp::SteamApp;
+
+/// A compatibility tool that Steam can run apps with, such as a build of Proton or the Steam Linux Runtime.
+///
+/// Valve's tools are installed as Steam apps, while custom tools such as GE-Proton are unpacked into a `compatibilitytools.d` folder.
+///
+/// # Example
+/// ```rust,no_run
+/// # use steamlocate::SteamDir;
+/// let mut steamdir = SteamDir::locate().unwrap();
+/// for tool in steamdir.compat_tools() {
+///     println!("{:?}: {}", tool.name, tool.path.display());
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CompatTool {
+    /// The internal name that [CompatToolMapping](struct.CompatToolMapping.html) refers to the tool by, e.g. `proton_8` or `GE-Proton8-25`.
+    ///
+    /// Steam only records the names of its own tools in `appinfo.vdf`, so the names of Steam apps are either well-known or guessed from the folder that the tool is installed in. This is `None` for Steam apps that don't look like one of Valve's tools.
+    pub name: Option<String>,
+    /// The name that Steam shows for the tool, e.g. `Proton 8.0`.
+    pub display_name: Option<String>,
+    /// The folder that the tool is installed in, which contains its `toolmanifest.vdf`.
+    pub path: PathBuf,
+    /// `commandline`: The command that Steam runs the tool with, e.g. `/proton %verb%`.
+    ///
+    /// It starts with a path relative to [`path`](#structfield.path).
+    pub commandline: Option<String>,
+    /// `require_tool_appid`: The app ID of another tool that this one runs inside of, e.g. Proton requires the Steam Linux Runtime.
+    pub require_tool_appid: Option<u32>,
+    /// Where the tool was installed from.
+    pub source: CompatToolSource,
+}
+
+/// Where a [CompatTool](struct.CompatTool.html) was installed from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum CompatToolSource {
+    /// The tool is the Steam app with the given app ID.
+    App(u32),
+    /// The tool was installed into a `compatibilitytools.d` folder.
+    Custom,
+}
+
+/// The internal names of Valve's tools, which Steam only has in the binary `appinfo.vdf`
+///
+/// Keep this up to date as Valve releases new tools. Tools missing from here fall back to
+/// [`name_from_install_dir`], which follows the same naming scheme
+const KNOWN_TOOLS: &[(u32, &str)] = &[
+    (858280, "proton_37"),
+    (930400, "proton_37_beta"),
+    (961940, "proton_316"),
+    (1054830, "proton_42"),
+    (1070560, "steamlinuxruntime"),
+    (1113280, "proton_411"),
+    (1245040, "proton_5"),
+    (1391110, "steamlinuxruntime_soldier"),
+    (1420170, "proton_513"),
+    (1493710, "proton_experimental"),
+    (1580130, "proton_63"),
+    (1628350, "steamlinuxruntime_sniper"),
+    (1887720, "proton_7"),
+    (2180100, "proton_hotfix"),
+    (2348590, "proton_8"),
+    (2805730, "proton_9"),
+];
+
+/// Guesses the internal name of one of Valve's tools from the folder it's installed in, e.g.
+/// `Proton 5.13` is `proton_513`, `Proton 8.0` is `proton_8` and `Proton - Experimental` is
+/// `proton_experimental`
+pub(crate) fn name_from_install_dir(install_dir: &str) -> Option<String> {
+    let install_dir = install_dir.to_ascii_lowercase();
+    if install_dir.starts_with("steamlinuxruntime") {
+        return Some(install_dir);
+    }
+
+    let version = install_dir
+        .strip_prefix("proton ")?
+        .trim_start_matches(['-', ' ']);
+    let name = match version.split_once('.') {
+        Some((major, "0")) => major.to_string(),
+        Some((major, minor)) => format!("{}{}", major, minor),
+        None => version.to_string(),
+    };
+    let is_valid = !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric());
+    Some(format!("proton_{}", name)).filter(|_| is_valid)
+}
+
+/// Returns the tool that `app` provides, or `None` if it has no `toolmanifest.vdf`
+pub(crate) fn from_app(app: &SteamApp) -> Result<Option<CompatTool>> {
+    let manifest_path = app.path.join("toolmanifest.vdf");
+    if !manifest_path.is_file() {
+        return Ok(None);
+    }
+
+    let manifest = read_tool_manifest(&manifest_path)?;
+    Ok(Some(CompatTool {
+        name: KNOWN_TOOLS
+            .iter()
+            .find(|(app_id, _)| *app_id == app.appid)
+            .map(|(_, name)| name.to_string())
+            .or_else(|| name_from_install_dir(app.path.file_name()?.to_str()?)),
+        display_name: app.name.clone(),
+        path: app.path.clone(),
+        commandline: manifest.commandline,
+        require_tool_appid: manifest.require_tool_appid,
+        source: CompatToolSource::App(app.appid),
+    }))
+}
+
+/// The folders that Steam looks for custom tools in
+///
+/// Besides the Steam installation's own folder, Steam checks the system-wide ones that
+/// distributions package tools into and the colon-separated `STEAM_EXTRA_COMPAT_TOOLS_PATHS`.
+/// Those belong to the running system, so they're only included when `is_host` is set
+pub(crate) fn custom_tool_dirs(steam_dir: &Path, is_host: bool) -> Vec<PathBuf> {
+    let mut dirs = vec![steam_dir.join("compatibilitytools.d")];
+    if !is_host {
+        return dirs;
+    }
+    if let Some(extra) = std::env::var_os("STEAM_EXTRA_COMPAT_TOOLS_PATHS") {
+        dirs.extend(std::env::split_paths(&extra));
+    }
+    if cfg!(target_os = "linux") {
+        dirs.push(PathBuf::from("/usr/share/steam/compatibilitytools.d"));
+        dirs.push(PathBuf::from("/usr/local/share/steam/compatibilitytools.d"));
+    }
+    dirs
+}
+
+/// Reads the custom tools from every `<dir>/*/compatibilitytool.vdf`, which looks like
+///
+/// ```vdf
+/// "compatibilitytools"
+/// {
+///     "compat_tools"
+///     {
+///         "GE-Proton8-25"
+///         {
+///             "install_path"    "."
+///             "display_name"    "GE-Proton8-25"
+///             ...
+///         }
+///     }
+/// }
+/// ```
+///
+/// When `strict` is set, the first error encountered is returned instead of the offending
+/// folder or tool being skipped
+pub(crate) fn discover_custom(dirs: &[PathBuf], strict: bool) -> Result<Vec<CompatTool>> {
+    let mut tools = Vec::new();
+    for dir in dirs.iter().filter(|dir| dir.is_dir()) {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(err) if strict => return Err(Error::io(err, dir)),
+            Err(_) => continue,
+        };
+        let mut tool_dirs: Vec<_> = entries
+            .filter_map(|entry| Some(entry.ok()?.path()))
+            .filter(|path| path.join("compatibilitytool.vdf").is_file())
+            .collect();
+        tool_dirs.sort();
+
+        for tool_dir in tool_dirs {
+            match read_custom_tools(&tool_dir) {
+                Ok(found) => tools.extend(found),
+                Err(err) if strict => return Err(err),
+                Err(_) => {}
+            }
+        }
+    }
+    Ok(tools)
+}
+
+fn read_custom_tools(tool_dir: &Path) -> Result<Vec<CompatTool>> {
+    let path = tool_dir.join("compatibilitytool.vdf");
+    let vdf_text = keyvalues::read(&path)?;
+    let value = Vdf::parse(&vdf_text)
+        .map_err(|err| Error::keyvalues(err, &path))?
+        .value;
+    let compat_tools = value
+        .get_obj()
+        .and_then(|obj| keyvalues::get_obj(obj, "compat_tools"))
+        .ok_or_else(|| Error::missing_key(&path, "compat_tools"))?;
+
+    compat_tools
+        .iter()
+        .filter_map(|(name, values)| Some((name, values.first()?.get_obj()?)))
+        .map(|(name, obj)| read_custom_tool(tool_dir, name, obj))
+        .collect()
+}
+
+fn read_custom_tool(tool_dir: &Path, name: &str, obj: &Obj) -> Result<CompatTool> {
+    // Usually `.`, but it may also be an absolute path
+    let path = match keyvalues::get_str(obj, "install_path") {
+        Some(".") | None => tool_dir.to_owned(),
+        Some(install_path) => tool_dir.join(install_path),
+    };
+    let manifest_path = path.join("toolmanifest.vdf");
+    let manifest = if manifest_path.is_file() {
+        read_tool_manifest(&manifest_path)?
+    } else {
+        ToolManifest::default()
+    };
+
+    Ok(CompatTool {
+        name: Some(name.to_string()),
+        display_name: keyvalues::get_str(obj, "display_name").map(|name| name.to_string()),
+        path,
+        commandline: manifest.commandline,
+        require_tool_appid: manifest.require_tool_appid,
+        source: CompatToolSource::Custom,
+    })
+}
+
+#[derive(Default)]
+struct ToolManifest {
+    commandline: Option<String>,
+    require_tool_appid: Option<u32>,
+}
+
+fn read_tool_manifest(path: &Path) -> Result<ToolManifest> {
+    let vdf_text = keyvalues::read(path)?;
+    let value = Vdf::parse(&vdf_text)
+        .map_err(|err| Error::keyvalues(err, path))?
+        .value;
+    let obj = value
+        .get_obj()
+        .ok_or_else(|| Error::unsupported_layout(path, "expected an object"))?;
+
+    Ok(ToolManifest {
+        commandline: keyvalues::get_str(obj, "commandline")
+            .map(|commandline| commandline.to_string()),
+        require_tool_appid: keyvalues::get_str(obj, "require_tool_appid")
+            .and_then(|app_id| app_id.parse().ok()),
+    })
+}
+
+/// Follows the tool that `app_id` is set to run with through every tool that it requires
+///
+/// The tools are returned in the order that they're required in, so the last one is the
+/// outermost tool that Steam actually starts
+///
+/// Only the app's own entry in `mapping` is used. Steam only applies the default tool to games
+/// without a native version, which can't be told from the files on disk
+pub(crate) fn resolve_chain(
+    app_id: u32,
+    mapping: &CompatToolMapping,
+    tools: &[CompatTool],
+) -> Result<Vec<CompatTool>> {
+    let name = match mapping.apps.get(&app_id) {
+        Some(configured) => &configured.name,
+        None => return Ok(Vec::new()),
+    };
+    let mut tool = tools
+        .iter()
+        .find(|tool| tool.name.as_ref() == Some(name))
+        .ok_or_else(|| Error::missing_compat_tool(app_id, name))?;
+
+    let mut chain = vec![tool.clone()];
+    while let Some(required) = tool.require_tool_appid {
+        tool = tools
+            .iter()
+            .find(|tool| tool.source == CompatToolSource::App(required))
+            .ok_or_else(|| Error::missing_compat_tool(app_id, format!("app {}", required)))?;
+        if chain.contains(tool) {
+            return Err(Error::unsupported_layout(
+                &tool.path,
+                "the tool's `require_tool_appid` leads back to itself",
+            ));
+        }
+        chain.push(tool.clone());
+    }
+
+    Ok(chain)
+}
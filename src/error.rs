@@ -0,0 +1,192 @@
+use std::{
+    fmt, io,
+    path::{Path, PathBuf},
+};
+
+/// A `Result` alias for `steamlocate`'s [`Error`](enum.Error.html) type
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// An error encountered while discovering Steam installations, libraries, apps or shortcuts.
+///
+/// This is returned by the `try_*` variants of the discovery functions. Their non-`try` counterparts silently discard these errors instead.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// No Steam installation could be located on this computer.
+    FailedLocate,
+
+    /// An I/O error occurred while accessing `path`.
+    Io { inner: io::Error, path: PathBuf },
+
+    /// The VDF file at `path` could not be parsed.
+    ///
+    /// `position` is provided when the parser reports where the error occurred.
+    Parse {
+        path: PathBuf,
+        position: Option<Position>,
+        message: String,
+    },
+
+    /// The VDF file at `path` is missing the expected `key`.
+    MissingKey { path: PathBuf, key: &'static str },
+
+    /// The `installdir` of an app manifest does not point to a valid directory.
+    InvalidInstallDir { app_id: u32, path: PathBuf },
+
+    /// The directory or file at `path` does not have the layout that Steam uses.
+    UnsupportedLayout { path: PathBuf, reason: String },
+
+    /// The compatibility `tool` that launching `app_id` needs isn't installed.
+    ///
+    /// `tool` is either the tool's internal name, such as `proton_8`, or `app <app_id>` for a tool that another tool requires.
+    MissingCompatTool { app_id: u32, tool: String },
+
+    /// Steam is running, so the file at `path` wasn't modified.
+    ///
+    /// Steam keeps its own copy of the files it has loaded and overwrites any changes when it exits.
+    SteamRunning { path: PathBuf },
+}
+
+/// Where a parsing error occurred within a file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Position {
+    /// A 1-based line and column, used for text files.
+    LineColumn { line: usize, column: usize },
+    /// A 0-based byte offset, used for binary files.
+    Offset(usize),
+}
+
+impl Error {
+    pub(crate) fn io(inner: io::Error, path: &Path) -> Self {
+        Error::Io {
+            inner,
+            path: path.to_owned(),
+        }
+    }
+
+    pub(crate) fn parse(
+        path: &Path,
+        position: Option<Position>,
+        message: impl Into<String>,
+    ) -> Self {
+        Error::Parse {
+            path: path.to_owned(),
+            position,
+            message: message.into(),
+        }
+    }
+
+    pub(crate) fn keyvalues(err: keyvalues_parser::error::Error, path: &Path) -> Self {
+        let position = match &err {
+            keyvalues_parser::error::Error::ParseError(pest_err) => match pest_err.line_col {
+                pest::error::LineColLocation::Pos((line, column))
+                | pest::error::LineColLocation::Span((line, column), _) => {
+                    Some(Position::LineColumn { line, column })
+                }
+            },
+            keyvalues_parser::error::Error::InvalidTokenStream(_) => None,
+        };
+
+        Error::parse(path, position, err.to_string())
+    }
+
+    pub(crate) fn steamy_vdf(err: steamy_vdf::Error, path: &Path) -> Self {
+        match err {
+            steamy_vdf::Error::Io(inner) => Error::io(inner, path),
+            err => Error::parse(path, None, err.to_string()),
+        }
+    }
+
+    pub(crate) fn binary_vdf(err: crate::binary_vdf::ParseError, path: &Path) -> Self {
+        Error::parse(path, Some(Position::Offset(err.offset)), err.message)
+    }
+
+    pub(crate) fn missing_key(path: &Path, key: &'static str) -> Self {
+        Error::MissingKey {
+            path: path.to_owned(),
+            key,
+        }
+    }
+
+    pub(crate) fn missing_compat_tool(app_id: u32, tool: impl Into<String>) -> Self {
+        Error::MissingCompatTool {
+            app_id,
+            tool: tool.into(),
+        }
+    }
+
+    pub(crate) fn steam_running(path: &Path) -> Self {
+        Error::SteamRunning {
+            path: path.to_owned(),
+        }
+    }
+
+    pub(crate) fn unsupported_layout(path: &Path, reason: impl Into<String>) -> Self {
+        Error::UnsupportedLayout {
+            path: path.to_owned(),
+            reason: reason.into(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::FailedLocate => f.write_str("failed to locate a Steam installation"),
+            Error::Io { inner, path } => {
+                write!(f, "I/O error accessing {}: {}", path.display(), inner)
+            }
+            Error::Parse {
+                path,
+                position,
+                message,
+            } => {
+                write!(f, "failed to parse {}", path.display())?;
+                if let Some(position) = position {
+                    write!(f, " at {}", position)?;
+                }
+                write!(f, ": {}", message)
+            }
+            Error::MissingKey { path, key } => {
+                write!(f, "{} is missing the key \"{}\"", path.display(), key)
+            }
+            Error::InvalidInstallDir { app_id, path } => write!(
+                f,
+                "the install directory of app {} is not a directory: {}",
+                app_id,
+                path.display()
+            ),
+            Error::UnsupportedLayout { path, reason } => {
+                write!(f, "unsupported layout at {}: {}", path.display(), reason)
+            }
+            Error::MissingCompatTool { app_id, tool } => write!(
+                f,
+                "the compatibility tool {} needed to launch app {} is not installed",
+                tool, app_id
+            ),
+            Error::SteamRunning { path } => write!(
+                f,
+                "refusing to modify {} while Steam is running",
+                path.display()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { inner, .. } => Some(inner),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Position::LineColumn { line, column } => write!(f, "line {}, column {}", line, column),
+            Position::Offset(offset) => write!(f, "byte offset {}", offset),
+        }
+    }
+}
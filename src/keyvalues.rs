@@ -0,0 +1,260 @@
+//! Helpers for navigating the text VDF files parsed with `keyvalues_parser`
+//!
+//! Steam isn't consistent with the casing of keys (`Software/Valve/Steam` can just as well be
+//! `Software/valve/Steam`), so lookups here ignore ASCII case
+
+use std::{fs, path::Path};
+
+use keyvalues_parser::{Obj, Value};
+
+use crate::error::{Error, Result};
+
+/// Reads the text VDF file at `path`, which can then be parsed with `keyvalues_parser::Vdf::parse`
+pub(crate) fn read(path: &Path) -> Result<String> {
+    fs::read_to_string(path).map_err(|err| Error::io(err, path))
+}
+
+/// Gets the first value for `key`
+pub(crate) fn get<'a>(obj: &'a Obj<'a>, key: &str) -> Option<&'a Value<'a>> {
+    obj.iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(key))
+        .and_then(|(_, values)| values.first())
+}
+
+pub(crate) fn get_str<'a>(obj: &'a Obj, key: &str) -> Option<&'a str> {
+    get(obj, key)?.get_str()
+}
+
+pub(crate) fn get_obj<'a>(obj: &'a Obj<'a>, key: &str) -> Option<&'a Obj<'a>> {
+    get(obj, key)?.get_obj()
+}
+
+/// Follows a chain of nested objects, e.g. `&["Software", "Valve", "Steam"]`
+pub(crate) fn get_obj_path<'a>(obj: &'a Obj<'a>, keys: &[&str]) -> Option<&'a Obj<'a>> {
+    keys.iter().try_fold(obj, |obj, key| get_obj(obj, key))
+}
+
+/// Sets the string at the end of a chain of nested objects, returning the edited text
+///
+/// This works on the text itself instead of re-rendering a parsed `Vdf`, which would sort the
+/// keys and lose comments and formatting. Only the value is replaced, and when the key or any of
+/// the objects leading to it are missing they're added at the end of the deepest existing object,
+/// indented with tabs like Steam does. `keys` starts inside the root object, just like
+/// [`get_obj_path`]
+///
+/// Returns `None` if `text` isn't valid VDF, there's no root object, or one of `keys` holds a
+/// string where an object is expected
+pub(crate) fn set_str_path(text: &str, keys: &[&str], value: &str) -> Option<String> {
+    let (last, objects) = keys.split_last()?;
+    let tokens = tokenize(text)?;
+    let mut index = 0;
+    let document = parse_entries(text, &tokens, &mut index, false)?;
+    let mut obj = match document.entries.first() {
+        Some((_, Entry::Obj(root))) => root,
+        _ => return None,
+    };
+
+    let mut depth = 1;
+    for (i, key) in objects.iter().enumerate() {
+        match obj.get(key) {
+            Some(Entry::Obj(child)) => obj = child,
+            Some(Entry::Str(_)) => return None,
+            None => return Some(insert(text, obj, depth, &objects[i..], last, value)),
+        }
+        depth += 1;
+    }
+
+    match obj.get(last) {
+        Some(Entry::Str(range)) => {
+            let mut edited = text.to_string();
+            edited.replace_range(range.clone(), &quote(value));
+            Some(edited)
+        }
+        Some(Entry::Obj(_)) => None,
+        None => Some(insert(text, obj, depth, &[], last, value)),
+    }
+}
+
+/// Adds `objects`, nested in each other, to the end of `obj`, with `key` set to `value` inside
+/// the innermost one
+fn insert(
+    text: &str,
+    obj: &Object,
+    depth: usize,
+    objects: &[&str],
+    key: &str,
+    value: &str,
+) -> String {
+    let indent = |level: usize| "\t".repeat(level);
+    let mut added = String::new();
+    for (level, object) in objects.iter().enumerate() {
+        let level = depth + level;
+        added += &format!("{0}{1}\n{0}{{\n", indent(level), quote(object));
+    }
+    added += &format!(
+        "{}{}\t\t{}\n",
+        indent(depth + objects.len()),
+        quote(key),
+        quote(value)
+    );
+    for level in (0..objects.len()).rev() {
+        added += &format!("{}}}\n", indent(depth + level));
+    }
+
+    // Objects are closed on a line of their own, which the new entries go right before
+    let close = obj.close;
+    let line_start = text[..close].rfind('\n').map_or(0, |newline| newline + 1);
+    let mut edited = text.to_string();
+    if text[line_start..close].trim().is_empty() {
+        edited.insert_str(line_start, &added);
+    } else {
+        edited.insert_str(close, &format!("\n{}{}", added, indent(depth - 1)));
+    }
+    edited
+}
+
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+enum Token {
+    /// A quoted or unquoted string, along with its byte range in the text
+    Str(String, std::ops::Range<usize>),
+    Open,
+    /// The closing brace, along with its byte offset in the text
+    Close(usize),
+}
+
+fn tokenize(text: &str) -> Option<Vec<Token>> {
+    let bytes = text.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b if b.is_ascii_whitespace() => i += 1,
+            b'/' if bytes.get(i + 1) == Some(&b'/') => {
+                i = text[i..]
+                    .find('\n')
+                    .map_or(bytes.len(), |newline| i + newline);
+            }
+            // Conditionals like `[$WIN32]` don't change the layout of the file
+            b'[' => i += text[i..].find(']')? + 1,
+            b'{' => {
+                tokens.push(Token::Open);
+                i += 1;
+            }
+            b'}' => {
+                tokens.push(Token::Close(i));
+                i += 1;
+            }
+            b'"' => {
+                let start = i;
+                i += 1;
+                loop {
+                    match bytes.get(i)? {
+                        b'\\' => i += 2,
+                        b'"' => break,
+                        _ => i += 1,
+                    }
+                }
+                i += 1;
+                tokens.push(Token::Str(text[start + 1..i - 1].to_string(), start..i));
+            }
+            _ => {
+                let start = i;
+                while i < bytes.len()
+                    && !bytes[i].is_ascii_whitespace()
+                    && !matches!(bytes[i], b'{' | b'}' | b'"')
+                {
+                    i += 1;
+                }
+                tokens.push(Token::Str(text[start..i].to_string(), start..i));
+            }
+        }
+    }
+    Some(tokens)
+}
+
+enum Entry {
+    Str(std::ops::Range<usize>),
+    Obj(Object),
+}
+
+struct Object {
+    entries: Vec<(String, Entry)>,
+    /// The byte offset of the closing brace, or the end of the text for the document itself
+    close: usize,
+}
+
+impl Object {
+    fn get(&self, key: &str) -> Option<&Entry> {
+        self.entries
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, entry)| entry)
+    }
+}
+
+/// Parses entries until the closing brace of a `nested` object, or the end of the document
+fn parse_entries(text: &str, tokens: &[Token], index: &mut usize, nested: bool) -> Option<Object> {
+    let mut entries = Vec::new();
+    loop {
+        let key = match tokens.get(*index) {
+            Some(Token::Str(key, _)) => key.clone(),
+            Some(Token::Close(offset)) if nested => {
+                *index += 1;
+                return Some(Object {
+                    entries,
+                    close: *offset,
+                });
+            }
+            None if !nested => {
+                return Some(Object {
+                    entries,
+                    close: text.len(),
+                })
+            }
+            _ => return None,
+        };
+        *index += 1;
+        let entry = match tokens.get(*index)? {
+            Token::Str(_, range) => Entry::Str(range.clone()),
+            Token::Open => {
+                *index += 1;
+                Entry::Obj(parse_entries(text, tokens, index, true)?)
+            }
+            Token::Close(_) => return None,
+        };
+        if let Entry::Str(_) = entry {
+            *index += 1;
+        }
+        entries.push((key, entry));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_str_path_edits_in_place() {
+        let text = "// comment\n\"root\"\n{\n\t\"a\"\t\t\"1\"\n\t\"B\" { \"c\" \"2\" }\n}\n";
+
+        assert_eq!(
+            set_str_path(text, &["b", "c"], "say \"hi\"").unwrap(),
+            "// comment\n\"root\"\n{\n\t\"a\"\t\t\"1\"\n\t\"B\" { \"c\" \"say \\\"hi\\\"\" }\n}\n"
+        );
+        assert_eq!(
+            set_str_path(text, &["B", "d"], "3").unwrap(),
+            "// comment\n\"root\"\n{\n\t\"a\"\t\t\"1\"\n\t\"B\" { \"c\" \"2\" \n\t\t\"d\"\t\t\"3\"\n\t}\n}\n"
+        );
+        assert_eq!(
+            set_str_path(text, &["e", "f"], "4").unwrap(),
+            "// comment\n\"root\"\n{\n\t\"a\"\t\t\"1\"\n\t\"B\" { \"c\" \"2\" }\n\t\"e\"\n\t{\n\t\t\"f\"\t\t\"4\"\n\t}\n}\n"
+        );
+
+        assert_eq!(set_str_path(text, &["a", "b"], "5"), None);
+        assert_eq!(set_str_path(text, &["B"], "5"), None);
+        assert_eq!(set_str_path("\"root\" { \"a\" ", &["a"], "5"), None);
+    }
+}
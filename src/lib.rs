@@ -24,8 +24,11 @@
 //!
 //! If you need to get uncached results, simply instantiate a new [SteamDir](https://docs.rs/steamlocate/*/steamlocate/struct.SteamDir.html).
 //!
+//! # Error handling
+//! The discovery functions return `None` or empty collections when something goes wrong. If you need to know why, use their `try_*` counterparts ([SteamDir::try_locate](struct.SteamDir.html#method.try_locate), [SteamDir::try_apps](struct.SteamDir.html#method.try_apps), etc.) which return a [steamlocate::Error](enum.Error.html) instead.
+//!
 //! # steamid-ng Support
-//! This crate supports [steamid-ng](https://docs.rs/steamid-ng) and can automatically convert [SteamApp::last_user](struct.SteamApp.html#structfield.last_user) to a [SteamID](https://docs.rs/steamid-ng/*/steamid_ng/struct.SteamID.html) for you.
+//! This crate has its own [SteamId](struct.SteamId.html) type, which is used by [SteamApp::last_user](struct.SteamApp.html#structfield.last_user) and [SteamUser](struct.SteamUser.html). It supports [steamid-ng](https://docs.rs/steamid-ng) by converting to and from a [SteamID](https://docs.rs/steamid-ng/*/steamid_ng/struct.SteamID.html) with `From`.
 //!
 //! To enable this support, [use the  `steamid_ng` Cargo.toml feature](#using-steamlocate).
 //!
@@ -64,8 +67,9 @@
 //!     appid: u32: 4000,
 //!     path: PathBuf: "C:\\Program Files (x86)\\steamapps\\common\\GarrysMod",
 //!     vdf: <steamy_vdf::Table>,
+//!     manifest: AppManifest { .. },
 //!     name: Some(String: "Garry's Mod"),
-//!     last_user: Some(u64: 76561198040894045)
+//!     last_user: Some(SteamId(76561198040894045))
 //! )
 //! ```
 //!
@@ -86,8 +90,9 @@
 //!         appid: u32: 4000,
 //!         path: PathBuf: "C:\\Program Files (x86)\\steamapps\\common\\GarrysMod",
 //!         vdf: <steamy_vdf::Table>,
+//!         manifest: AppManifest { .. },
 //!         name: Some(String: "Garry's Mod"),
-//!         last_user: Some(u64: 76561198040894045)
+//!         last_user: Some(SteamId(76561198040894045))
 //!     )
 //!     ...
 //! }
@@ -118,7 +123,11 @@
 #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
 compile_error!("Unsupported operating system!");
 
-use std::{collections::HashMap, path::PathBuf};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
 
 #[cfg(target_os = "windows")]
 use winreg::{
@@ -128,20 +137,67 @@ use winreg::{
 #[cfg(not(target_os = "windows"))]
 extern crate dirs;
 
+mod error;
+mod keyvalues;
+pub use error::{Error, Position, Result};
+
+mod appmanifest;
+pub use appmanifest::{AppManifest, Depot};
+
+mod appstate;
+pub use appstate::AppState;
+
 #[doc(hidden)]
 pub mod steamapp;
-pub use steamapp::SteamApp;
+pub use steamapp::{AppEntry, AppStatus, SteamApp};
 
 #[doc(hidden)]
 pub mod libraryfolders;
-pub use libraryfolders::LibraryFolders;
+pub use libraryfolders::{LibraryFolder, LibraryFolders};
 
 mod steamapps;
 use steamapps::SteamApps;
 
+pub mod binary_vdf;
+
 mod shortcut;
 pub use shortcut::Shortcut;
 
+mod artwork;
+pub use artwork::{Artwork, ArtworkKind};
+
+mod steamuser;
+pub use steamuser::SteamUser;
+
+mod steamid;
+pub use steamid::{ParseSteamIdError, SteamId};
+
+mod localconfig;
+pub use localconfig::{AppLocalConfig, UserLocalConfig};
+
+mod compatmapping;
+pub use compatmapping::{AppCompatTool, CompatToolMapping};
+
+mod compatdata;
+pub use compatdata::CompatData;
+
+mod registry;
+pub use registry::{RegistryFile, RegistryHive, RegistryKey, RegistryValue};
+
+mod protonprefix;
+pub use protonprefix::{KnownFolder, ProtonPrefix};
+
+mod compattool;
+pub use compattool::{CompatTool, CompatToolSource};
+
+mod running;
+mod write;
+
+#[cfg(feature = "shortcuts_extras")]
+mod shortcuts_file;
+#[cfg(feature = "shortcuts_extras")]
+pub use shortcuts_file::ShortcutsFile;
+
 /// An instance of a Steam installation.
 ///
 /// All functions of this struct will cache their results.
@@ -165,9 +221,31 @@ pub struct SteamDir {
     ///
     /// Example: `C:\Program Files (x86)\Steam`
     pub path: PathBuf,
+    /// How this Steam installation was found.
+    pub kind: SteamDirKind,
     pub(crate) steam_apps: SteamApps,
     pub(crate) libraryfolders: LibraryFolders,
     pub(crate) shortcuts: Option<Vec<Shortcut>>,
+    pub(crate) users: Option<Vec<SteamUser>>,
+    /// Whether this is the installation of the running system rather than one found under another root
+    pub(crate) is_host: bool,
+}
+
+/// How a [SteamDir](struct.SteamDir.html) was found.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SteamDirKind {
+    /// A regular installation of Steam, including distribution packages on Linux.
+    #[default]
+    Native,
+    /// The `com.valvesoftware.Steam` Flatpak (Linux).
+    Flatpak,
+    /// The `steam` Snap package (Linux).
+    Snap,
+    /// A directory given explicitly through [SteamDir::from_dir](struct.SteamDir.html#method.from_dir).
+    Custom,
+    /// A directory given through an environment variable, see [SteamDir::locate](struct.SteamDir.html#method.locate).
+    Environment,
 }
 
 impl SteamDir {
@@ -184,6 +262,17 @@ impl SteamDir {
         &*libraryfolders
     }
 
+    /// Like [`libraryfolders`](#method.libraryfolders), but returns an error if `libraryfolders.vdf` or `config/config.vdf` can't be read or parsed.
+    ///
+    /// A successful result is cached. Once [`libraryfolders`](#method.libraryfolders) has been called, its best-effort result is returned instead.
+    pub fn try_libraryfolders(&mut self) -> Result<&LibraryFolders> {
+        let libraryfolders = &mut self.libraryfolders;
+        if !libraryfolders.discovered {
+            libraryfolders.try_discover(&self.path)?;
+        }
+        Ok(&*libraryfolders)
+    }
+
     /// Returns a reference to `HashMap<u32, Option<SteamApp>>` of all `SteamApp`s located on this computer.
     ///
     /// All `Option<SteamApp>`s in this context will be `Some`, so you can safely `unwrap()` them without panicking.
@@ -203,8 +292,9 @@ impl SteamDir {
     ///         appid: u32: 4000,
     ///         path: PathBuf: "C:\\Program Files (x86)\\steamapps\\common\\GarrysMod",
     ///         vdf: <steamy_vdf::Table>,
+    ///         manifest: AppManifest { .. },
     ///         name: Some(String: "Garry's Mod"),
-    ///         last_user: Some(u64: 76561198040894045) // This will be a steamid_ng::SteamID if the "steamid_ng" feature is enabled
+    ///         last_user: Some(SteamId(76561198040894045))
     ///     )
     ///     ...
     /// }
@@ -221,6 +311,98 @@ impl SteamDir {
         &steam_apps.apps
     }
 
+    /// Like [`apps`](#method.apps), but returns the first error encountered while reading the library folders or their app manifests.
+    ///
+    /// Apps whose install directory doesn't exist are still recorded as `None`. Use [`try_app`](#method.try_app) to find out why a specific app is missing.
+    ///
+    /// A successful result is cached.
+    pub fn try_apps(&mut self) -> Result<&HashMap<u32, Option<SteamApp>>> {
+        let steam_apps = &mut self.steam_apps;
+        if !steam_apps.discovered {
+            let libraryfolders = &mut self.libraryfolders;
+            if !libraryfolders.discovered {
+                libraryfolders.try_discover(&self.path)?;
+            }
+            steam_apps.try_discover_apps(libraryfolders)?;
+        }
+        Ok(&steam_apps.apps)
+    }
+
+    /// Returns every app found in the library folders, including the ones that [`apps`](#method.apps) and [`app`](#method.app) treat as not installed.
+    ///
+    /// Each entry's [`status`](struct.AppEntry.html#structfield.status) tells whether the app is usable, which makes it possible to tell an app that isn't installed apart from one that is installed but broken. Entries are ordered by library folder and then by app ID.
+    ///
+    /// This function will cache its results.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # use steamlocate::{SteamDir, AppStatus};
+    /// let mut steamdir = SteamDir::locate().unwrap();
+    /// for entry in steamdir.app_entries() {
+    ///     if entry.status != AppStatus::Installed {
+    ///         println!("{} needs repairing: {:?}", entry.app_id, entry.status);
+    ///     }
+    /// }
+    /// ```
+    pub fn app_entries(&mut self) -> &[AppEntry] {
+        let steam_apps = &mut self.steam_apps;
+        if steam_apps.entries.is_none() {
+            let libraryfolders = &mut self.libraryfolders;
+            if !libraryfolders.discovered {
+                libraryfolders.discover(&self.path);
+            }
+            steam_apps.discover_entries(libraryfolders);
+        }
+        steam_apps.entries.as_ref().unwrap()
+    }
+
+    /// Returns every entry found for an app, starting with the canonical one that [`apps`](#method.apps) and [`app`](#method.app) use.
+    ///
+    /// An app usually has a single manifest, but copying a library folder by hand can leave one in several libraries. Steam only uses one of them, so the canonical entry is the first of these that applies:
+    ///
+    /// 1. Its install directory exists.
+    /// 2. Its library's `apps` in `libraryfolders.vdf` list the app.
+    /// 3. Its manifest's `LastUpdated` is the most recent.
+    /// 4. Its library comes first in `libraryfolders.vdf`.
+    ///
+    /// This uses the same cache as [`app_entries`](#method.app_entries).
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # use steamlocate::SteamDir;
+    /// let mut steamdir = SteamDir::locate().unwrap();
+    /// for entry in steamdir.app_candidates(&4000) {
+    ///     println!("{} (canonical: {})", entry.manifest_path.display(), entry.is_canonical);
+    /// }
+    /// ```
+    pub fn app_candidates(&mut self, app_id: &u32) -> Vec<&AppEntry> {
+        let mut candidates: Vec<&AppEntry> = self
+            .app_entries()
+            .iter()
+            .filter(|entry| entry.app_id == *app_id)
+            .collect();
+        // Stable, so the rest stay in library folder order
+        candidates.sort_by_key(|entry| !entry.is_canonical);
+        candidates
+    }
+
+    /// Returns the apps that have a manifest in more than one library folder, along with all of their entries as ordered by [`app_candidates`](#method.app_candidates).
+    ///
+    /// Entries for libraries that are offline aren't counted since their manifests can't be read.
+    pub fn duplicate_apps(&mut self) -> HashMap<u32, Vec<&AppEntry>> {
+        let mut duplicates: HashMap<u32, Vec<&AppEntry>> = HashMap::new();
+        for entry in self.app_entries() {
+            if entry.status != AppStatus::LibraryOffline {
+                duplicates.entry(entry.app_id).or_default().push(entry);
+            }
+        }
+        duplicates.retain(|_, entries| entries.len() > 1);
+        for entries in duplicates.values_mut() {
+            entries.sort_by_key(|entry| !entry.is_canonical);
+        }
+        duplicates
+    }
+
     /// Returns a `Some` reference to a `SteamApp` via its app ID.
     ///
     /// If the Steam app is not installed on the system, this will return `None`.
@@ -239,8 +421,9 @@ impl SteamDir {
     ///     appid: u32: 4000,
     ///     path: PathBuf: "C:\\Program Files (x86)\\steamapps\\common\\GarrysMod",
     ///     vdf: <steamy_vdf::Table>,
+    ///     manifest: AppManifest { .. },
     ///     name: Some(String: "Garry's Mod"),
-    ///     last_user: Some(u64: 76561198040894045) // This will be a steamid_ng::SteamID if the "steamid_ng" feature is enabled
+    ///     last_user: Some(SteamId(76561198040894045))
     /// )
     /// ```
     pub fn app(&mut self, app_id: &u32) -> Option<&SteamApp> {
@@ -259,6 +442,25 @@ impl SteamDir {
         steam_apps.apps.get(app_id).unwrap().as_ref()
     }
 
+    /// Like [`app`](#method.app), but returns an error explaining why an app with a manifest couldn't be loaded, such as its install directory missing.
+    ///
+    /// Returns `Ok(None)` if no library folder contains a manifest for the app. Only apps that were found are cached.
+    pub fn try_app(&mut self, app_id: &u32) -> Result<Option<&SteamApp>> {
+        let steam_apps = &mut self.steam_apps;
+
+        // A cached `None` doesn't say why the app is missing, so look for it again
+        if !matches!(steam_apps.apps.get(app_id), Some(Some(_))) {
+            let libraryfolders = &mut self.libraryfolders;
+            if !libraryfolders.discovered {
+                libraryfolders.try_discover(&self.path)?;
+            }
+            let app = steam_apps.try_discover_app(libraryfolders, app_id)?;
+            steam_apps.apps.insert(*app_id, app);
+        }
+
+        Ok(steam_apps.apps.get(app_id).unwrap().as_ref())
+    }
+
     /// Returns a listing of all added non-Steam games
     pub fn shortcuts(&mut self) -> &[Shortcut] {
         if self.shortcuts.is_none() {
@@ -269,93 +471,625 @@ impl SteamDir {
         self.shortcuts.as_ref().unwrap()
     }
 
-    /// Locates the Steam installation directory on the filesystem and initializes a `SteamDir` (Windows)
+    /// Returns the non-Steam games added by the Steam user with the given account ID, which is the name of their `userdata/<account_id>` folder.
     ///
-    /// Returns `None` if no Steam installation can be located.
-    #[cfg(target_os = "windows")]
+    /// This uses the same cache as [`shortcuts`](#method.shortcuts).
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # use steamlocate::SteamDir;
+    /// let mut steamdir = SteamDir::locate().unwrap();
+    /// for shortcut in steamdir.shortcuts_for_user(12345678) {
+    ///     println!("{}", shortcut.app_name);
+    /// }
+    /// ```
+    pub fn shortcuts_for_user(&mut self, account_id: u32) -> Vec<&Shortcut> {
+        self.shortcuts()
+            .iter()
+            .filter(|shortcut| shortcut.account_id == Some(account_id))
+            .collect()
+    }
+
+    /// Like [`shortcuts`](#method.shortcuts), but returns an error if any `shortcuts.vdf` file can't be read or parsed.
+    ///
+    /// A successful result is cached.
+    pub fn try_shortcuts(&mut self) -> Result<&[Shortcut]> {
+        if self.shortcuts.is_none() {
+            let shortcuts = shortcut::try_discover_shortcuts(&self.path)?;
+            self.shortcuts = Some(shortcuts);
+        }
+
+        Ok(self.shortcuts.as_ref().unwrap())
+    }
+
+    /// Returns the Steam accounts that have logged in on this computer, ordered by SteamID.
+    ///
+    /// This function will cache its results.
+    pub fn users(&mut self) -> &[SteamUser] {
+        if self.users.is_none() {
+            self.users = Some(steamuser::discover_users(&self.path).unwrap_or_default());
+        }
+
+        self.users.as_ref().unwrap()
+    }
+
+    /// Like [`users`](#method.users), but returns an error if `config/loginusers.vdf` can't be read or parsed.
+    ///
+    /// A successful result is cached.
+    pub fn try_users(&mut self) -> Result<&[SteamUser]> {
+        if self.users.is_none() {
+            self.users = Some(steamuser::discover_users(&self.path)?);
+        }
+
+        Ok(self.users.as_ref().unwrap())
+    }
+
+    /// Reads the per-app settings and usage of the user with the given account ID from their `userdata/<account_id>/config/localconfig.vdf`.
+    ///
+    /// Unlike most functions of this struct, this isn't cached since Steam keeps updating the file.
+    pub fn user_local_config(&self, account_id: u32) -> Result<UserLocalConfig> {
+        UserLocalConfig::load(&self.user_config_path(account_id, "localconfig.vdf"))
+    }
+
+    /// Sets the launch options that the user with the given account ID has for an app, such as `PROTON_LOG=1 %command%`.
+    ///
+    /// Only the app's `LaunchOptions` in `userdata/<account_id>/config/localconfig.vdf` is changed, everything else in the file is kept as it is. The previous file is kept next to it with a `.bak` extension.
+    ///
+    /// Returns [`Error::SteamRunning`](enum.Error.html#variant.SteamRunning) if Steam is running, since it would overwrite the change when it exits.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # use steamlocate::SteamDir;
+    /// let mut steamdir = SteamDir::locate().unwrap();
+    /// let user = steamdir.most_recent_user().unwrap().account_id();
+    /// steamdir.set_launch_options(user, 4000, "-novid").unwrap();
+    /// ```
+    pub fn set_launch_options(
+        &self,
+        account_id: u32,
+        app_id: u32,
+        launch_options: &str,
+    ) -> Result<()> {
+        let path = self.user_config_path(account_id, "localconfig.vdf");
+        if running::is_steam_running() {
+            return Err(Error::steam_running(&path));
+        }
+        localconfig::write_launch_options(&path, app_id, launch_options)
+    }
+
+    /// Like [`set_launch_options`](#method.set_launch_options), but for a non-Steam game, whose launch options are kept in `userdata/<account_id>/config/shortcuts.vdf`.
+    ///
+    /// Returns `false` if the user has no shortcut with the given app ID.
+    ///
+    /// This requires the `shortcuts_extras` feature.
+    #[cfg(feature = "shortcuts_extras")]
+    pub fn set_shortcut_launch_options(
+        &self,
+        account_id: u32,
+        appid: u32,
+        launch_options: &str,
+    ) -> Result<bool> {
+        let path = self.user_config_path(account_id, "shortcuts.vdf");
+        if running::is_steam_running() {
+            return Err(Error::steam_running(&path));
+        }
+
+        let mut file = ShortcutsFile::load(&path)?;
+        let shortcut = match file.get(appid) {
+            Some(shortcut) => Shortcut {
+                launch_options: launch_options.to_string(),
+                ..shortcut.clone()
+            },
+            None => return Ok(false),
+        };
+        file.update(shortcut);
+        file.save()?;
+        Ok(true)
+    }
+
+    /// Reads which compatibility tool, such as Proton, each app is set to run with from `config/config.vdf`.
+    ///
+    /// A Steam installation without the file has no tools set. Like [`user_local_config`](#method.user_local_config), this isn't cached.
+    pub fn compat_tool_mapping(&self) -> Result<CompatToolMapping> {
+        let path = self.path.join("config").join("config.vdf");
+        if !path.is_file() {
+            return Ok(CompatToolMapping {
+                path,
+                ..Default::default()
+            });
+        }
+
+        CompatToolMapping::load(&path)
+    }
+
+    /// Returns every compatibility tool that is installed, such as the builds of Proton.
+    ///
+    /// Tools that are Steam apps come first, ordered by app ID, followed by the custom tools in `compatibilitytools.d`. Besides the one in the Steam installation, the system-wide folders and the ones in `STEAM_EXTRA_COMPAT_TOOLS_PATHS` are searched as well when the installation was found by [`locate`](#method.locate) or [`locate_all`](#method.locate_all). Those belong to the running system, so they're skipped for [`from_dir`](#method.from_dir) and [`locate_in`](#method.locate_in).
+    ///
+    /// Only the list of apps is cached, the tools themselves are read fresh every time.
+    pub fn compat_tools(&mut self) -> Vec<CompatTool> {
+        let mut tools: Vec<_> = self
+            .apps()
+            .values()
+            .flatten()
+            .filter_map(|app| compattool::from_app(app).ok().flatten())
+            .collect();
+        tools.sort_by_key(|tool| tool.source);
+        tools.extend(
+            compattool::discover_custom(
+                &compattool::custom_tool_dirs(&self.path, self.is_host),
+                false,
+            )
+            .unwrap_or_default(),
+        );
+        tools
+    }
+
+    /// Like [`compat_tools`](#method.compat_tools), but returns the first error encountered while reading the apps or the tools' manifests.
+    pub fn try_compat_tools(&mut self) -> Result<Vec<CompatTool>> {
+        let mut tools = Vec::new();
+        for app in self.try_apps()?.values().flatten() {
+            tools.extend(compattool::from_app(app)?);
+        }
+        tools.sort_by_key(|tool| tool.source);
+        tools.extend(compattool::discover_custom(
+            &compattool::custom_tool_dirs(&self.path, self.is_host),
+            true,
+        )?);
+        Ok(tools)
+    }
+
+    /// Resolves every compatibility tool needed to launch an app outside of Steam, e.g. Proton and the Steam Linux Runtime container that Proton runs in.
+    ///
+    /// The tool that the app is set to run with (see [`compat_tool_mapping`](#method.compat_tool_mapping)) comes first, followed by the tool that it requires through `require_tool_appid` and so on. Steam starts the last tool, which runs the one before it, down to the app itself. Each tool's [`path`](struct.CompatTool.html#structfield.path) and [`commandline`](struct.CompatTool.html#structfield.commandline) give the command to run it with.
+    ///
+    /// Non-Steam games work the same way, using their shortcut's [`appid`](struct.Shortcut.html#structfield.appid). Apps that aren't set to run with a tool of their own give an empty list, even when Steam Play is enabled for all titles. Steam only uses that [`default`](struct.CompatToolMapping.html#structfield.default) tool for games without a native version, which can't be told from the files on disk.
+    ///
+    /// Returns [`Error::MissingCompatTool`](enum.Error.html#variant.MissingCompatTool) if any tool in the chain isn't installed.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # use steamlocate::SteamDir;
+    /// let mut steamdir = SteamDir::locate().unwrap();
+    /// for tool in steamdir.launch_chain(4000).unwrap() {
+    ///     println!("{} {:?}", tool.path.display(), tool.commandline);
+    /// }
+    /// ```
+    pub fn launch_chain(&mut self, app_id: u32) -> Result<Vec<CompatTool>> {
+        let mapping = self.compat_tool_mapping()?;
+        let tools = self.compat_tools();
+        compattool::resolve_chain(app_id, &mapping, &tools)
+    }
+
+    /// Finds the Proton prefix of an app or non-Steam game, see [CompatData](struct.CompatData.html).
+    ///
+    /// An installed app's own library folder is searched first, followed by every library folder in order. Returns `None` if the app has never been run through Proton.
+    ///
+    /// This uses the same caches as [`app`](#method.app) and [`libraryfolders`](#method.libraryfolders).
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # use steamlocate::SteamDir;
+    /// let mut steamdir = SteamDir::locate().unwrap();
+    /// if let Some(compat_data) = steamdir.compat_data(4000) {
+    ///     println!("{} ({:?})", compat_data.pfx.display(), compat_data.config_info);
+    /// }
+    /// ```
+    pub fn compat_data(&mut self, app_id: u32) -> Option<CompatData> {
+        // `path` is `<library>/steamapps/common/<installdir>`
+        let steamapps = self
+            .app(&app_id)
+            .and_then(|app| app.path.parent()?.parent())
+            .map(Path::to_owned);
+        compatdata::find(app_id, steamapps.as_deref(), self.libraryfolders())
+    }
+
+    fn user_config_path(&self, account_id: u32, file_name: &str) -> PathBuf {
+        self.path
+            .join("userdata")
+            .join(account_id.to_string())
+            .join("config")
+            .join(file_name)
+    }
+
+    /// Returns the user that logged in last, which is usually the one that Steam logs in as when it starts.
+    ///
+    /// This uses the same cache as [`users`](#method.users).
+    pub fn most_recent_user(&mut self) -> Option<&SteamUser> {
+        steamuser::most_recent(self.users())
+    }
+
+    /// Like [`locate`](#method.locate), but returns an error explaining why no Steam installation could be located.
+    ///
+    /// When a location was found but can't be used, such as `STEAMLOCATE_STEAM_DIR` pointing to a missing directory, this is an [`Error::Io`](enum.Error.html#variant.Io) or [`Error::UnsupportedLayout`](enum.Error.html#variant.UnsupportedLayout) with the path that was checked. Otherwise it is [`Error::FailedLocate`](enum.Error.html#variant.FailedLocate).
+    pub fn try_locate() -> Result<SteamDir> {
+        let steamdir = match locate_from_env(|key| std::env::var_os(key), Path::new(HOST_ROOT)) {
+            Some(steamdir) => steamdir,
+            None => Self::try_locate_platform(),
+        };
+        steamdir.map(SteamDir::on_host)
+    }
+
+    /// Marks the installation as the running system's, so that system-wide settings apply to it
+    fn on_host(self) -> SteamDir {
+        SteamDir {
+            is_host: true,
+            ..self
+        }
+    }
+
+    /// Locates the Steam installation directory on the filesystem and initializes a `SteamDir`
+    ///
+    /// The following are checked in order:
+    ///
+    /// 1. The `STEAMLOCATE_STEAM_DIR` environment variable. When it is set, it is the only location that is checked.
+    /// 2. The `STEAM_COMPAT_CLIENT_INSTALL_PATH` environment variable, which Steam sets when launching games through Proton and other compatibility tools.
+    /// 3. The platform's usual install locations. On Windows this is read from the registry, on macOS it is `~/Library/Application Support/Steam` and on Linux it is the first installation found by [`locate_all`](#method.locate_all).
+    ///
+    /// On Linux, paths from environment variables are also looked up under `/run/host`, which is where the host's filesystem is mounted inside the Steam Linux Runtime (pressure-vessel) container, and the reverse when running outside of the container.
+    ///
+    /// Returns `None` if no Steam installation can be located, see [`try_locate`](#method.try_locate) to find out why.
     pub fn locate() -> Option<SteamDir> {
+        Self::try_locate().ok()
+    }
+
+    #[cfg(target_os = "windows")]
+    fn try_locate_platform() -> Result<SteamDir> {
         // Locating the Steam installation location is a bit more complicated on Windows
 
         // Steam's installation location can be found in the registry
         let hklm = RegKey::predef(HKEY_LOCAL_MACHINE);
-        let installation_regkey = match
-			hklm.open_subkey_with_flags("SOFTWARE\\Wow6432Node\\Valve\\Steam", KEY_READ).or_else(|_| // 32-bit
-			hklm.open_subkey_with_flags("SOFTWARE\\Valve\\Steam", KEY_READ)) // 64-bit
-		{
-		    Ok(installation_regkey) => installation_regkey,
-		    Err(_) => return None
-		};
+        let registry_path = Path::new("HKEY_LOCAL_MACHINE\\SOFTWARE\\Valve\\Steam");
+        let installation_regkey = hklm
+            .open_subkey_with_flags("SOFTWARE\\Wow6432Node\\Valve\\Steam", KEY_READ) // 32-bit
+            .or_else(|_| hklm.open_subkey_with_flags("SOFTWARE\\Valve\\Steam", KEY_READ)) // 64-bit
+            .map_err(|err| Error::io(err, registry_path))?;
 
         // The InstallPath key will contain the full path to the Steam directory
-        let install_path_str: String = match installation_regkey.get_value("InstallPath") {
-            Ok(install_path_str) => install_path_str,
-            Err(_) => return None,
-        };
+        let install_path_str: String = installation_regkey
+            .get_value("InstallPath")
+            .map_err(|err| Error::io(err, &registry_path.join("InstallPath")))?;
 
         let install_path = PathBuf::from(install_path_str);
+        check_steam_root(&install_path)?;
 
-        Some(SteamDir {
+        Ok(SteamDir {
             path: install_path,
             ..Default::default()
         })
     }
 
-    /// Locates the Steam installation directory on the filesystem and initializes a `SteamDir` (macOS)
-    ///
-    /// Returns `None` if no Steam installation can be located.
     #[cfg(target_os = "macos")]
-    pub fn locate() -> Option<SteamDir> {
-        // Steam's installation location is pretty easy to find on macOS, as it's always in $USER/Library/Application Support
+    fn try_locate_platform() -> Result<SteamDir> {
+        let home_dir = dirs::home_dir().ok_or(Error::FailedLocate)?;
+        let install_path = home_dir.join("Library/Application Support/Steam");
+        check_steam_root(&install_path)?;
+
+        Ok(SteamDir {
+            path: install_path,
+            ..Default::default()
+        })
+    }
+
+    #[cfg(target_os = "linux")]
+    fn try_locate_platform() -> Result<SteamDir> {
+        let home_dir = dirs::home_dir().ok_or(Error::FailedLocate)?;
+        try_locate_linux(&home_dir, xdg_data_home())
+    }
+
+    /// Locates every Steam installation on the filesystem (Windows and macOS)
+    ///
+    /// There's only ever a single installation to find on these platforms. Unlike [`locate`](#method.locate), environment variables aren't taken into account.
+    #[cfg(not(target_os = "linux"))]
+    pub fn locate_all() -> Vec<SteamDir> {
+        Self::try_locate_platform()
+            .into_iter()
+            .map(SteamDir::on_host)
+            .collect()
+    }
+
+    /// Locates every Steam installation on the filesystem (Linux)
+    ///
+    /// Installations are returned in the following priority order. Paths which resolve to the same directory through symlinks are only returned once.
+    ///
+    /// 1. The Flatpak: `~/.var/app/com.valvesoftware.Steam/.steam/steam` and `~/.var/app/com.valvesoftware.Steam/.local/share/Steam`
+    /// 2. `~/.steam/steam` and `~/.steam/root`, the symlinks that Steam maintains to its active installation
+    /// 3. `$XDG_DATA_HOME/Steam` and `~/.local/share/Steam`
+    /// 4. `~/.steam/debian-installation`, used by Debian's `steam-installer` package
+    /// 5. The Snap: `~/snap/steam/common/.local/share/Steam`
+    ///
+    /// Unlike [`locate`](#method.locate), environment variables aren't taken into account.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use steamlocate::SteamDir;
+    /// for steamdir in SteamDir::locate_all() {
+    ///     println!("{:?}: {}", steamdir.kind, steamdir.path.display());
+    /// }
+    /// ```
+    #[cfg(target_os = "linux")]
+    pub fn locate_all() -> Vec<SteamDir> {
         let home_dir = match dirs::home_dir() {
             Some(home_dir) => home_dir,
-            None => return None,
+            None => return Vec::new(),
         };
 
+        locate_all_linux(&home_dir, xdg_data_home())
+            .into_iter()
+            .map(SteamDir::on_host)
+            .collect()
+    }
+
+    /// Locates a Steam installation relative to `home` instead of the current user's home directory (macOS)
+    ///
+    /// This runs the same search as [`locate`](#method.locate), which makes it possible to find the Steam installation of another user or on a mounted disk.
+    ///
+    /// Returns `None` if no Steam installation can be located.
+    #[cfg(target_os = "macos")]
+    pub fn locate_in<P: AsRef<Path>>(home: P) -> Option<SteamDir> {
+        // Steam's installation location is pretty easy to find on macOS, as it's always in $USER/Library/Application Support
+        let home_dir = home.as_ref();
+
         // Find Library/Application Support/Steam
         let install_path = home_dir.join("Library/Application Support/Steam");
-        return match install_path.is_dir() {
+        match install_path.is_dir() {
             false => None,
             true => Some(SteamDir {
                 path: install_path,
                 ..Default::default()
             }),
-        };
+        }
     }
 
-    /// Locates the Steam installation directory on the filesystem and initializes a `SteamDir` (Linux)
+    /// Locates a Steam installation relative to `home` instead of the current user's home directory (Linux)
+    ///
+    /// This runs the same search as [`locate`](#method.locate), which makes it possible to find the Steam installation of another user or on a mounted disk. `$XDG_DATA_HOME` belongs to the current user, so it is ignored.
     ///
     /// Returns `None` if no Steam installation can be located.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use steamlocate::SteamDir;
+    /// let steamdir = SteamDir::locate_in("/mnt/backup/home/alice");
+    /// ```
     #[cfg(target_os = "linux")]
-    pub fn locate() -> Option<SteamDir> {
-        // Steam's installation location is pretty easy to find on Linux, too, thanks to the symlink in $USER
-        let home_dir = match dirs::home_dir() {
-            Some(home_dir) => home_dir,
-            None => return None,
-        };
+    pub fn locate_in<P: AsRef<Path>>(home: P) -> Option<SteamDir> {
+        Self::locate_all_in(home).into_iter().next()
+    }
 
-        // Check for Flatpak steam install
-        let steam_flatpak_path = home_dir.join(".var/app/com.valvesoftware.Steam");
-        if steam_flatpak_path.is_dir() {
-            let steam_flatpak_install_path = steam_flatpak_path.join(".steam/steam");
-            if steam_flatpak_install_path.is_dir() {
-                return Some(SteamDir {
-                    path: steam_flatpak_install_path,
-                    ..Default::default()
-                });
+    /// Locates a Steam installation under `root` instead of the running system's registry (Windows)
+    ///
+    /// `root` is usually the root of a mounted drive or a backup of one. Steam records where it is installed in the registry of the system that it runs on, so the default install folders `Program Files (x86)\Steam` and `Program Files\Steam` are checked instead.
+    ///
+    /// Returns `None` if no Steam installation can be located.
+    ///
+    /// # Example
+    /// ```rust
+    /// # use steamlocate::SteamDir;
+    /// let steamdir = SteamDir::locate_in("D:\\");
+    /// ```
+    #[cfg(target_os = "windows")]
+    pub fn locate_in<P: AsRef<Path>>(root: P) -> Option<SteamDir> {
+        Self::locate_all_in(root).into_iter().next()
+    }
+
+    /// Locates every Steam installation under `root` instead of the running system's registry (Windows)
+    ///
+    /// See [`locate_in`](#method.locate_in).
+    #[cfg(target_os = "windows")]
+    pub fn locate_all_in<P: AsRef<Path>>(root: P) -> Vec<SteamDir> {
+        ["Program Files (x86)\\Steam", "Program Files\\Steam"]
+            .iter()
+            .map(|install_dir| root.as_ref().join(install_dir))
+            .filter(|path| path.is_dir())
+            .map(|path| SteamDir {
+                path,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    /// Locates every Steam installation relative to `home` instead of the current user's home directory (macOS)
+    #[cfg(target_os = "macos")]
+    pub fn locate_all_in<P: AsRef<Path>>(home: P) -> Vec<SteamDir> {
+        Self::locate_in(home).into_iter().collect()
+    }
+
+    /// Locates every Steam installation relative to `home` instead of the current user's home directory (Linux)
+    ///
+    /// See [`locate_all`](#method.locate_all) and [`locate_in`](#method.locate_in).
+    #[cfg(target_os = "linux")]
+    pub fn locate_all_in<P: AsRef<Path>>(home: P) -> Vec<SteamDir> {
+        locate_all_linux(home.as_ref(), None)
+    }
+
+    /// Initializes a `SteamDir` from the path of an existing Steam installation, such as a backup or a test fixture.
+    ///
+    /// The directory must contain the `steamapps`, `config` and `userdata` directories that every Steam installation has, otherwise [`Error::UnsupportedLayout`](enum.Error.html#variant.UnsupportedLayout) is returned.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # use steamlocate::SteamDir;
+    /// let mut steamdir = SteamDir::from_dir("/mnt/backup/Steam").unwrap();
+    /// println!("{:#?}", steamdir.apps());
+    /// ```
+    pub fn from_dir<P: AsRef<Path>>(path: P) -> Result<SteamDir> {
+        let path = path.as_ref();
+
+        let metadata = fs::metadata(path).map_err(|err| Error::io(err, path))?;
+        if !metadata.is_dir() {
+            return Err(Error::unsupported_layout(path, "not a directory"));
+        }
+
+        for expected in &["steamapps", "config", "userdata"] {
+            if !path.join(expected).is_dir() {
+                return Err(Error::unsupported_layout(
+                    path,
+                    format!("missing the `{}` directory", expected),
+                ));
             }
         }
 
-        // Check for Standard steam install
-        let standard_path = home_dir.join(".steam/steam");
-        if standard_path.is_dir() {
-            return Some(SteamDir {
-                path: standard_path,
+        Ok(SteamDir {
+            path: path.to_owned(),
+            kind: SteamDirKind::Custom,
+            ..Default::default()
+        })
+    }
+}
+
+/// Where the host's filesystem is mounted inside of the Steam Linux Runtime container
+const HOST_ROOT: &str = "/run/host";
+
+/// Checks that `path` is a directory, which a Steam installation could be in
+fn check_steam_root(path: &Path) -> Result<()> {
+    let metadata = fs::metadata(path).map_err(|err| Error::io(err, path))?;
+    if !metadata.is_dir() {
+        return Err(Error::unsupported_layout(path, "not a directory"));
+    }
+    Ok(())
+}
+
+/// Checks the environment variables that can point to a Steam installation
+///
+/// Returns `None` if none of them are set, or an error if the explicit override is set to
+/// something that isn't a directory
+fn locate_from_env<F>(var: F, host_root: &Path) -> Option<Result<SteamDir>>
+where
+    F: Fn(&str) -> Option<std::ffi::OsString>,
+{
+    let from_var = |key: &str| {
+        var(key).filter(|value| !value.is_empty()).map(|value| {
+            let path = translate_host_path(PathBuf::from(value), host_root);
+            check_steam_root(&path)?;
+            Ok(SteamDir {
+                path,
+                kind: SteamDirKind::Environment,
                 ..Default::default()
-            });
-        }
+            })
+        })
+    };
 
-        None
+    if var("STEAMLOCATE_STEAM_DIR").is_some_and(|value| !value.is_empty()) {
+        return from_var("STEAMLOCATE_STEAM_DIR");
     }
+
+    // Steam may have set this for a different installation, so it's only a hint
+    from_var("STEAM_COMPAT_CLIENT_INSTALL_PATH")
+        .and_then(Result::ok)
+        .map(Ok)
+}
+
+/// Translates a path between the host and the Steam Linux Runtime container if it only exists on
+/// the other side of `host_root`
+#[cfg(target_os = "linux")]
+fn translate_host_path(path: PathBuf, host_root: &Path) -> PathBuf {
+    if path.exists() || !path.is_absolute() {
+        return path;
+    }
+
+    let translated = match path.strip_prefix(host_root) {
+        // A path from inside of the container while we're running on the host
+        Ok(relative) => Path::new("/").join(relative),
+        // A path from the host while we're running inside of the container
+        Err(_) => host_root.join(path.strip_prefix("/").unwrap_or(&path)),
+    };
+
+    if translated.exists() {
+        translated
+    } else {
+        path
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn translate_host_path(path: PathBuf, _host_root: &Path) -> PathBuf {
+    path
+}
+
+/// Lists every location that Steam may be installed to on Linux in priority order
+#[cfg(target_os = "linux")]
+fn linux_candidates(
+    home_dir: &Path,
+    xdg_data_home: Option<PathBuf>,
+) -> Vec<(SteamDirKind, PathBuf)> {
+    let flatpak_dir = home_dir.join(".var/app/com.valvesoftware.Steam");
+    let xdg_data_home = xdg_data_home.unwrap_or_else(|| home_dir.join(".local/share"));
+
+    vec![
+        (SteamDirKind::Flatpak, flatpak_dir.join(".steam/steam")),
+        (
+            SteamDirKind::Flatpak,
+            flatpak_dir.join(".local/share/Steam"),
+        ),
+        (SteamDirKind::Native, home_dir.join(".steam/steam")),
+        (SteamDirKind::Native, home_dir.join(".steam/root")),
+        (SteamDirKind::Native, xdg_data_home.join("Steam")),
+        (SteamDirKind::Native, home_dir.join(".local/share/Steam")),
+        (
+            SteamDirKind::Native,
+            home_dir.join(".steam/debian-installation"),
+        ),
+        (
+            SteamDirKind::Snap,
+            home_dir.join("snap/steam/common/.local/share/Steam"),
+        ),
+    ]
+}
+
+/// `$XDG_DATA_HOME`, which the spec says to ignore unless it's absolute
+#[cfg(target_os = "linux")]
+fn xdg_data_home() -> Option<PathBuf> {
+    std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+}
+
+/// Returns the first installation that [`locate_all_linux`] would, or why the first candidate
+/// that exists can't be used
+#[cfg(target_os = "linux")]
+fn try_locate_linux(home_dir: &Path, xdg_data_home: Option<PathBuf>) -> Result<SteamDir> {
+    let mut first_error = None;
+    for (kind, path) in linux_candidates(home_dir, xdg_data_home) {
+        // Most of the candidates don't exist, which isn't worth reporting
+        if fs::symlink_metadata(&path).is_err() {
+            continue;
+        }
+        match check_steam_root(&path) {
+            Ok(()) => {
+                return Ok(SteamDir {
+                    path,
+                    kind,
+                    ..Default::default()
+                })
+            }
+            Err(err) => {
+                first_error.get_or_insert(err);
+            }
+        }
+    }
+
+    Err(first_error.unwrap_or(Error::FailedLocate))
+}
+
+#[cfg(target_os = "linux")]
+fn locate_all_linux(home_dir: &Path, xdg_data_home: Option<PathBuf>) -> Vec<SteamDir> {
+    let mut seen = std::collections::HashSet::new();
+
+    linux_candidates(home_dir, xdg_data_home)
+        .into_iter()
+        .filter(|(_, path)| path.is_dir())
+        .filter(|(_, path)| {
+            // `~/.steam/steam` and friends are usually symlinks to one of the other candidates
+            let canonical = path.canonicalize().unwrap_or_else(|_| path.clone());
+            seen.insert(canonical)
+        })
+        .map(|(kind, path)| SteamDir {
+            path,
+            kind,
+            ..Default::default()
+        })
+        .collect()
 }
 
 #[cfg(test)]
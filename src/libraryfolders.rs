@@ -1,9 +1,12 @@
 use std::{
-    fs,
+    collections::{HashMap, HashSet},
     path::{Path, PathBuf},
 };
 
-use keyvalues_parser::Vdf;
+use keyvalues_parser::{Obj, Value, Vdf};
+
+use crate::error::{Error, Result};
+use crate::keyvalues;
 
 /// An instance which contains all the Steam library folders installed on the file system.
 /// Example:
@@ -30,9 +33,76 @@ pub struct LibraryFolders {
     ///
     /// This will always include the Steam installation directory's `SteamApps` folder.
     pub paths: Vec<PathBuf>,
+    /// Every library folder along with the details that `libraryfolders.vdf` records about it.
+    ///
+    /// These are in the same order as [`paths`](#structfield.paths).
+    pub folders: Vec<LibraryFolder>,
     pub(crate) discovered: bool,
 }
 
+/// A single Steam library folder, as described by an entry in `libraryfolders.vdf`.
+///
+/// Details other than the `path` are only known for libraries listed in the modern `libraryfolders.vdf` format.
+#[derive(Default, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct LibraryFolder {
+    /// The path to the library folder. Unlike [`LibraryFolders::paths`](struct.LibraryFolders.html#structfield.paths), this does not include `steamapps`.
+    ///
+    /// Example: `D:\SteamLibrary`
+    pub path: PathBuf,
+    /// The label given to the library in Steam's storage manager. This is usually empty.
+    pub label: Option<String>,
+    /// An identifier that Steam changes whenever the contents of the library change.
+    pub content_id: Option<i64>,
+    /// The total size of the drive that the library is on in bytes, or `0` if it's unknown.
+    pub total_size: Option<u64>,
+    /// Bytes of updates that Steam has applied to the library without detecting corruption.
+    pub update_clean_bytes_tally: Option<u64>,
+    /// The Unix timestamp of the last time an update detected corruption in the library, or `0` if it never has.
+    pub time_last_update_corruption: Option<u64>,
+    /// A map of the app IDs installed in this library to their size on disk in bytes.
+    pub apps: HashMap<u32, u64>,
+}
+
+impl LibraryFolder {
+    pub(crate) fn new(path: PathBuf) -> Self {
+        LibraryFolder {
+            path,
+            ..Default::default()
+        }
+    }
+
+    /// The path to the `steamapps` folder within this library, which contains the app manifests.
+    pub fn steamapps(&self) -> PathBuf {
+        self.path.join("steamapps")
+    }
+
+    fn from_obj(obj: &Obj) -> Option<Self> {
+        let apps = keyvalues::get_obj(obj, "apps")
+            .map(|apps| {
+                apps.iter()
+                    .filter_map(|(app_id, values)| {
+                        let size = values.first()?.get_str()?.parse().ok()?;
+                        Some((app_id.parse().ok()?, size))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Some(LibraryFolder {
+            path: PathBuf::from(keyvalues::get_str(obj, "path")?),
+            label: keyvalues::get_str(obj, "label").map(|label| label.to_string()),
+            content_id: keyvalues::get_str(obj, "contentid").and_then(|id| id.parse().ok()),
+            total_size: keyvalues::get_str(obj, "totalsize").and_then(|size| size.parse().ok()),
+            update_clean_bytes_tally: keyvalues::get_str(obj, "update_clean_bytes_tally")
+                .and_then(|tally| tally.parse().ok()),
+            time_last_update_corruption: keyvalues::get_str(obj, "time_last_update_corruption")
+                .and_then(|time| time.parse().ok()),
+            apps,
+        })
+    }
+}
+
 impl LibraryFolders {
     /// Discovers all the steam libraries from `libraryfolders.vdf`
     ///
@@ -55,41 +125,245 @@ impl LibraryFolders {
     ///     ...
     /// }
     /// ```
+    ///
+    /// Older versions of Steam instead map each number straight to a path, and don't list the Steam
+    /// installation itself
+    ///
+    /// ```vdf
+    /// "LibraryFolders"
+    /// {
+    ///     ...
+    ///     "1"    "/path/to/second/library"
+    /// }
+    /// ```
+    ///
+    /// Even older versions kept the libraries in `config/config.vdf` as `BaseInstallFolder_<N>` keys
+    /// under `InstallConfigStore/Software/Valve/Steam`. All of these are merged together
     pub(crate) fn discover(&mut self, path: &Path) {
-        let _ = self._discover(path);
+        // Fall back to the Steam installation's own library so that callers still get something
+        let mut folders = read_libraryfolders_vdf(path)
+            .unwrap_or_else(|_| vec![LibraryFolder::new(path.to_owned())]);
+        // The legacy folders are only ever extra, so losing them shouldn't cost the ones found above
+        folders.extend(read_base_install_folders(path).unwrap_or_default());
+
+        self.set_folders(dedup_folders(folders));
+        self.discovered = true;
     }
 
-    fn _discover(&mut self, path: &Path) -> Option<()> {
-        let steamapps = path.join("steamapps");
-        self.paths.push(steamapps.clone());
-
-        let libraryfolders_vdf_path = steamapps.join("libraryfolders.vdf");
-        if libraryfolders_vdf_path.is_file() {
-            let vdf_text = fs::read_to_string(&libraryfolders_vdf_path).ok()?;
-            let value = Vdf::parse(&vdf_text).ok()?.value;
-            let obj = value.get_obj()?;
-
-            let library_folders: Vec<_> = obj
-                .iter()
-                .filter(|(key, values)| key.parse::<u32>().is_ok() && values.len() == 1)
-                .filter_map(|(_, values)| {
-                    let library_folder_string = values
-                        .get(0)?
-                        .get_obj()?
-                        .get("path")?
-                        .get(0)?
-                        .get_str()?
-                        .to_string();
-                    let library_folder = PathBuf::from(library_folder_string).join("steamapps");
-                    Some(library_folder)
-                })
-                .collect();
-
-            self.paths = library_folders;
-        }
+    pub(crate) fn try_discover(&mut self, path: &Path) -> Result<()> {
+        let mut folders = read_libraryfolders_vdf(path)?;
+        folders.extend(read_base_install_folders(path)?);
 
+        self.set_folders(dedup_folders(folders));
         self.discovered = true;
 
-        Some(())
+        Ok(())
+    }
+
+    fn set_folders(&mut self, folders: Vec<LibraryFolder>) {
+        self.paths = folders.iter().map(LibraryFolder::steamapps).collect();
+        self.folders = folders;
+    }
+}
+
+/// Reads the library folders listed in `steamapps/libraryfolders.vdf`, always including the Steam
+/// installation itself
+fn read_libraryfolders_vdf(path: &Path) -> Result<Vec<LibraryFolder>> {
+    let mut folders = Vec::new();
+    let mut is_modern_format = false;
+
+    let libraryfolders_vdf_path = path.join("steamapps").join("libraryfolders.vdf");
+    if libraryfolders_vdf_path.is_file() {
+        let vdf_text = keyvalues::read(&libraryfolders_vdf_path)?;
+        let value = Vdf::parse(&vdf_text)
+            .map_err(|err| Error::keyvalues(err, &libraryfolders_vdf_path))?
+            .value;
+        let obj = value.get_obj().ok_or_else(|| {
+            Error::unsupported_layout(&libraryfolders_vdf_path, "expected an object")
+        })?;
+
+        // The keys are sorted as strings, which would put `10` before `2`
+        let mut numbered: Vec<_> = obj
+            .iter()
+            .filter(|(_, values)| values.len() == 1)
+            .filter_map(|(key, values)| Some((key.parse::<u32>().ok()?, values.first()?)))
+            .collect();
+        numbered.sort_by_key(|(index, _)| *index);
+
+        folders = numbered
+            .into_iter()
+            .filter_map(|(_, value)| match value {
+                Value::Obj(obj) => {
+                    is_modern_format = true;
+                    LibraryFolder::from_obj(obj)
+                }
+                Value::Str(path) => Some(LibraryFolder::new(PathBuf::from(path.as_ref()))),
+            })
+            .collect();
+    }
+
+    // Only the modern format lists the Steam installation itself
+    if !is_modern_format {
+        folders.insert(0, LibraryFolder::new(path.to_owned()));
+    }
+
+    Ok(folders)
+}
+
+/// Reads the `BaseInstallFolder_<N>` libraries from `config/config.vdf`
+fn read_base_install_folders(path: &Path) -> Result<Vec<LibraryFolder>> {
+    let config_vdf_path = path.join("config").join("config.vdf");
+    if !config_vdf_path.is_file() {
+        return Ok(Vec::new());
+    }
+
+    let vdf_text = keyvalues::read(&config_vdf_path)?;
+    let vdf = Vdf::parse(&vdf_text).map_err(|err| Error::keyvalues(err, &config_vdf_path))?;
+    let folders = vdf
+        .value
+        .get_obj()
+        .and_then(|obj| keyvalues::get_obj_path(obj, &["Software", "Valve", "Steam"]))
+        .into_iter()
+        .flat_map(|steam| steam.iter())
+        .filter(|(key, _)| {
+            key.get(.."BaseInstallFolder_".len())
+                .is_some_and(|prefix| prefix.eq_ignore_ascii_case("BaseInstallFolder_"))
+        })
+        .filter_map(|(_, values)| values.first()?.get_str())
+        .map(|path| LibraryFolder::new(PathBuf::from(path)))
+        .collect();
+
+    Ok(folders)
+}
+
+/// Removes library folders that point to the same directory, keeping the first occurrence
+fn dedup_folders(folders: Vec<LibraryFolder>) -> Vec<LibraryFolder> {
+    let mut seen = HashSet::new();
+    folders
+        .into_iter()
+        .filter(|folder| {
+            let canonical = folder
+                .path
+                .canonicalize()
+                .unwrap_or_else(|_| folder.path.clone());
+            seen.insert(canonical)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Position;
+
+    #[test]
+    fn parse_error_has_position() {
+        let steam_dir =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/sample_data/broken_steam");
+        let mut libraryfolders = LibraryFolders::default();
+
+        match libraryfolders.try_discover(&steam_dir) {
+            Err(Error::Parse { path, position, .. }) => {
+                assert!(path.ends_with("steamapps/libraryfolders.vdf"));
+                assert!(matches!(position, Some(Position::LineColumn { .. })));
+            }
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+        assert!(!libraryfolders.discovered);
+
+        // The infallible version still falls back to the installation's own library
+        libraryfolders.discover(&steam_dir);
+        assert_eq!(libraryfolders.paths, vec![steam_dir.join("steamapps")]);
+        assert!(libraryfolders.discovered);
+    }
+
+    #[test]
+    fn broken_config_keeps_libraries() {
+        let steam_dir =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/sample_data/broken_config");
+        let mut libraryfolders = LibraryFolders::default();
+
+        match libraryfolders.try_discover(&steam_dir) {
+            Err(Error::Parse { path, .. }) => assert!(path.ends_with("config/config.vdf")),
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+
+        // Only the legacy `BaseInstallFolder_<N>` libraries are lost
+        libraryfolders.discover(&steam_dir);
+        assert_eq!(
+            libraryfolders.paths,
+            vec![
+                PathBuf::from("/home/user/.steam/steam/steamapps"),
+                PathBuf::from("/mnt/games/SteamLibrary/steamapps"),
+            ]
+        );
+        assert!(libraryfolders.discovered);
+    }
+
+    #[test]
+    fn library_folder_details() {
+        let steam_dir =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/sample_data/libraryfolders");
+        let mut libraryfolders = LibraryFolders::default();
+        libraryfolders.try_discover(&steam_dir).unwrap();
+
+        assert_eq!(
+            libraryfolders.paths,
+            vec![
+                PathBuf::from("/home/user/.local/share/Steam/steamapps"),
+                PathBuf::from("/mnt/games/SteamLibrary/steamapps"),
+            ]
+        );
+
+        let main = &libraryfolders.folders[0];
+        assert_eq!(main.label.as_deref(), Some(""));
+        assert_eq!(main.content_id, Some(7498453584328226451));
+        assert_eq!(main.update_clean_bytes_tally, Some(2563441215));
+        assert_eq!(main.apps.get(&1628350), Some(&665012431));
+
+        let games = &libraryfolders.folders[1];
+        assert_eq!(games.label.as_deref(), Some("Games"));
+        assert_eq!(games.content_id, Some(-2318547711983254021));
+        assert_eq!(games.total_size, Some(1000186310656));
+        assert_eq!(games.time_last_update_corruption, Some(1699500640));
+        assert_eq!(games.apps.len(), 1);
+        assert_eq!(games.apps.get(&4000), Some(&4152333499));
+    }
+
+    #[test]
+    fn many_library_folders() {
+        let steam_dir =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/sample_data/many_libraryfolders");
+        let mut libraryfolders = LibraryFolders::default();
+        libraryfolders.try_discover(&steam_dir).unwrap();
+
+        // In the order that Steam lists them, not the order of their keys as strings
+        let expected: Vec<_> = (0..12)
+            .map(|index| PathBuf::from(format!("/mnt/library{}/steamapps", index)))
+            .collect();
+        assert_eq!(libraryfolders.paths, expected);
+    }
+
+    #[test]
+    fn legacy_library_folders() {
+        let steam_dir =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/sample_data/legacy_libraryfolders");
+        let mut libraryfolders = LibraryFolders::default();
+        libraryfolders.try_discover(&steam_dir).unwrap();
+
+        // The Steam installation comes first and `D:\SteamLibrary` is only listed once
+        assert_eq!(
+            libraryfolders.paths,
+            vec![
+                steam_dir.join("steamapps"),
+                PathBuf::from("D:\\SteamLibrary").join("steamapps"),
+                PathBuf::from("E:\\Games\\Steam").join("steamapps"),
+                PathBuf::from("F:\\SteamLibrary").join("steamapps"),
+            ]
+        );
+        assert_eq!(
+            libraryfolders.folders[1],
+            LibraryFolder::new("D:\\SteamLibrary".into())
+        );
     }
 }
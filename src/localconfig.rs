@@ -0,0 +1,137 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use keyvalues_parser::{Obj, Vdf};
+
+use crate::error::{Error, Result};
+use crate::keyvalues;
+use crate::write;
+
+/// A user's per-app settings and usage, read from `userdata/<account_id>/config/localconfig.vdf`.
+///
+/// Steam writes this file whenever the user changes a setting or finishes playing, so it is read fresh every time instead of being cached.
+///
+/// # Example
+/// ```rust,no_run
+/// # use steamlocate::SteamDir;
+/// let mut steamdir = SteamDir::locate().unwrap();
+/// let user = steamdir.most_recent_user().unwrap().account_id();
+/// let local_config = steamdir.user_local_config(user).unwrap();
+/// for (app_id, app) in local_config.recently_played().into_iter().take(5) {
+///     println!("{}: {:?} minutes", app_id, app.playtime);
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct UserLocalConfig {
+    /// The path to the `localconfig.vdf` file.
+    pub path: PathBuf,
+    /// The settings and usage of each app that the user has played or configured, keyed by app ID.
+    ///
+    /// This includes non-Steam games, which are keyed by their shortcut's app ID.
+    pub apps: HashMap<u32, AppLocalConfig>,
+}
+
+/// The settings and usage of a single app, see [UserLocalConfig](struct.UserLocalConfig.html).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct AppLocalConfig {
+    /// `LastPlayed`: The Unix timestamp of the last time the app was launched.
+    pub last_played: Option<u64>,
+    /// `Playtime`: The total time played in minutes.
+    pub playtime: Option<u64>,
+    /// `Playtime2wks`: The time played over the last two weeks in minutes.
+    pub playtime_2wks: Option<u64>,
+    /// `LaunchOptions`: The launch options that the user has set for the app.
+    pub launch_options: Option<String>,
+    /// `cloud/last_sync_state`: The state of Steam Cloud synchronization, such as `synchronized`.
+    pub cloud_sync_state: Option<String>,
+}
+
+impl UserLocalConfig {
+    /// Reads the `localconfig.vdf` file at `path`.
+    pub fn load(path: &Path) -> Result<Self> {
+        let vdf_text = keyvalues::read(path)?;
+        let value = Vdf::parse(&vdf_text)
+            .map_err(|err| Error::keyvalues(err, path))?
+            .value;
+        let obj = value
+            .get_obj()
+            .ok_or_else(|| Error::unsupported_layout(path, "expected an object"))?;
+
+        let apps = keyvalues::get_obj_path(obj, &["Software", "Valve", "Steam", "apps"])
+            .map(|apps| {
+                apps.iter()
+                    .filter_map(|(app_id, values)| {
+                        let app = values.first()?.get_obj()?;
+                        Some((app_id.parse().ok()?, AppLocalConfig::from_obj(app)))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(UserLocalConfig {
+            path: path.to_owned(),
+            apps,
+        })
+    }
+
+    /// Returns the settings and usage of the app with the given ID.
+    pub fn app(&self, app_id: u32) -> Option<&AppLocalConfig> {
+        self.apps.get(&app_id)
+    }
+
+    /// Returns the apps that have been played, most recently played first.
+    pub fn recently_played(&self) -> Vec<(u32, &AppLocalConfig)> {
+        let mut played: Vec<_> = self
+            .apps
+            .iter()
+            .filter(|(_, app)| app.last_played.is_some_and(|time| time > 0))
+            .map(|(app_id, app)| (*app_id, app))
+            .collect();
+        played.sort_by_key(|(app_id, app)| (std::cmp::Reverse(app.last_played), *app_id));
+        played
+    }
+}
+
+/// Sets the `LaunchOptions` of an app in the `localconfig.vdf` file at `path`, leaving the rest of the file untouched
+pub(crate) fn write_launch_options(path: &Path, app_id: u32, launch_options: &str) -> Result<()> {
+    let vdf_text = keyvalues::read(path)?;
+    // Parsing first gives a proper error for malformed files
+    Vdf::parse(&vdf_text).map_err(|err| Error::keyvalues(err, path))?;
+
+    let app_id = app_id.to_string();
+    let edited = keyvalues::set_str_path(
+        &vdf_text,
+        &[
+            "Software",
+            "Valve",
+            "Steam",
+            "apps",
+            &app_id,
+            "LaunchOptions",
+        ],
+        launch_options,
+    )
+    .ok_or_else(|| Error::unsupported_layout(path, "expected an object"))?;
+
+    write::replace_with_backup(path, edited.as_bytes())
+}
+
+impl AppLocalConfig {
+    fn from_obj(obj: &Obj) -> Self {
+        let int = |key| keyvalues::get_str(obj, key).and_then(|value| value.parse().ok());
+
+        AppLocalConfig {
+            last_played: int("LastPlayed"),
+            playtime: int("Playtime"),
+            playtime_2wks: int("Playtime2wks"),
+            launch_options: keyvalues::get_str(obj, "LaunchOptions").map(|s| s.to_string()),
+            cloud_sync_state: keyvalues::get_obj(obj, "cloud")
+                .and_then(|cloud| keyvalues::get_str(cloud, "last_sync_state"))
+                .map(|state| state.to_string()),
+        }
+    }
+}
@@ -0,0 +1,309 @@
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+};
+
+use crate::error::Result;
+use crate::registry::{RegistryFile, RegistryHive};
+
+/// The Wine prefix that Proton runs an app in, i.e. the `pfx` folder of its [CompatData](struct.CompatData.html).
+///
+/// The app sees the prefix as a Windows installation, so this finds the folders that games keep their saves and settings in, and translates between the paths that the game sees and the ones on the host.
+///
+/// # Example
+/// ```rust,no_run
+/// # use steamlocate::{KnownFolder, SteamDir};
+/// let mut steamdir = SteamDir::locate().unwrap();
+/// let prefix = steamdir.compat_data(4000).unwrap().prefix();
+/// println!("{}", prefix.known_folder(KnownFolder::Documents).display());
+/// println!("{:?}", prefix.to_unix("C:\\users\\steamuser\\AppData\\Roaming"));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ProtonPrefix {
+    /// The `pfx` folder.
+    pub path: PathBuf,
+}
+
+/// The Windows folders that games commonly keep files in, see [ProtonPrefix::known_folder](struct.ProtonPrefix.html#method.known_folder).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum KnownFolder {
+    /// `C:\users\steamuser\Documents`
+    Documents,
+    /// `C:\users\steamuser\AppData\Local`
+    AppDataLocal,
+    /// `C:\users\steamuser\AppData\LocalLow`
+    AppDataLocalLow,
+    /// `C:\users\steamuser\AppData\Roaming`
+    AppDataRoaming,
+    /// `C:\users\steamuser\Saved Games`
+    SavedGames,
+    /// `C:\users\steamuser\Desktop`
+    Desktop,
+    /// `C:\Program Files`
+    ProgramFiles,
+    /// `C:\Program Files (x86)`
+    ProgramFilesX86,
+}
+
+/// The user that Proton runs every app as
+const USER: &str = "steamuser";
+
+/// The user's profile relative to `drive_c`
+fn profile() -> PathBuf {
+    Path::new("users").join(USER)
+}
+
+impl KnownFolder {
+    /// The folder relative to `drive_c`, followed by where prefixes created by older versions of
+    /// Wine keep it
+    fn paths(self) -> (PathBuf, Option<PathBuf>) {
+        let user = |path: &str| profile().join(path);
+        match self {
+            KnownFolder::Documents => (user("Documents"), Some(user("My Documents"))),
+            KnownFolder::AppDataLocal => (
+                user("AppData/Local"),
+                Some(user("Local Settings/Application Data")),
+            ),
+            KnownFolder::AppDataLocalLow => (user("AppData/LocalLow"), None),
+            KnownFolder::AppDataRoaming => {
+                (user("AppData/Roaming"), Some(user("Application Data")))
+            }
+            KnownFolder::SavedGames => (user("Saved Games"), None),
+            KnownFolder::Desktop => (user("Desktop"), None),
+            KnownFolder::ProgramFiles => (PathBuf::from("Program Files"), None),
+            KnownFolder::ProgramFilesX86 => (PathBuf::from("Program Files (x86)"), None),
+        }
+    }
+}
+
+impl ProtonPrefix {
+    pub(crate) fn new(path: PathBuf) -> Self {
+        ProtonPrefix { path }
+    }
+
+    /// The folder that the app sees as `C:\`.
+    pub fn drive_c(&self) -> PathBuf {
+        self.path.join("drive_c")
+    }
+
+    /// The folder that the app sees as the user's profile, `C:\users\steamuser`.
+    pub fn user_dir(&self) -> PathBuf {
+        self.drive_c().join(profile())
+    }
+
+    /// The path to a known folder, which may not exist yet.
+    ///
+    /// Prefixes created by older versions of Proton use the Windows XP layout, such as `My Documents`, which is returned instead if it exists and the current one doesn't.
+    pub fn known_folder(&self, folder: KnownFolder) -> PathBuf {
+        let drive_c = self.drive_c();
+        let (current, legacy) = folder.paths();
+        let current = drive_c.join(current);
+        match legacy.map(|legacy| drive_c.join(legacy)) {
+            Some(legacy) if !current.exists() && legacy.exists() => legacy,
+            _ => current,
+        }
+    }
+
+    /// Loads one of the prefix's registry files, such as `system.reg`.
+    pub fn registry(&self, hive: RegistryHive) -> Result<RegistryFile> {
+        RegistryFile::load(&self.path.join(hive.file_name()))
+    }
+
+    /// The drives of the prefix and the folders that they point to, from the `dosdevices` symlinks
+    ///
+    /// Without `dosdevices`, which Wine creates when it sets the prefix up, this only has `C:`
+    fn drives(&self) -> Vec<(char, PathBuf)> {
+        let dosdevices = self.path.join("dosdevices");
+        let mut drives: Vec<_> = fs::read_dir(&dosdevices)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| {
+                let entry = entry.ok()?;
+                let name = entry.file_name().into_string().ok()?;
+                let mut chars = name.chars();
+                let letter = match (chars.next(), chars.next(), chars.next()) {
+                    (Some(letter), Some(':'), None) if letter.is_ascii_alphabetic() => letter,
+                    _ => return None,
+                };
+                // Relative links like `../drive_c` are relative to `dosdevices`
+                let target = dosdevices.join(fs::read_link(entry.path()).ok()?);
+                Some((letter.to_ascii_uppercase(), normalize(&target)))
+            })
+            .collect();
+
+        if !drives.iter().any(|(letter, _)| *letter == 'C') {
+            drives.push(('C', normalize(&self.drive_c())));
+        }
+        drives.sort();
+        drives
+    }
+
+    /// Translates a path as the app sees it, like `C:\users\steamuser\Documents` or `Z:\home\alice`, into the path on the host.
+    ///
+    /// Windows paths aren't case sensitive, so each part of the path is matched against the existing files ignoring case, and `..` never leaves the drive. Returns `None` if the path doesn't start with a drive that the prefix has.
+    pub fn to_unix(&self, windows_path: &str) -> Option<PathBuf> {
+        let mut chars = windows_path.chars();
+        let letter = match (chars.next(), chars.next()) {
+            (Some(letter), Some(':')) => letter.to_ascii_uppercase(),
+            _ => return None,
+        };
+        let (_, mut path) = self
+            .drives()
+            .into_iter()
+            .find(|(drive, _)| *drive == letter)?;
+        // Like on Windows, `..` at the root of a drive stays at the root
+        let root_depth = path.components().count();
+
+        for part in chars.as_str().split(['\\', '/']) {
+            match part {
+                "" | "." => {}
+                ".." => {
+                    if path.components().count() > root_depth {
+                        path.pop();
+                    }
+                }
+                part => path = join_ignoring_case(&path, part),
+            }
+        }
+        Some(path)
+    }
+
+    /// Translates a path on the host into the path that the app sees, like `C:\users\steamuser\Documents`.
+    ///
+    /// The drive that points to the deepest folder containing the path is used, so paths within the prefix get `C:` even though `Z:` contains everything. Returns `None` if no drive contains the path.
+    pub fn to_windows(&self, unix_path: &Path) -> Option<String> {
+        let path = fs::canonicalize(unix_path).unwrap_or_else(|_| normalize(unix_path));
+        let (letter, rest) = self
+            .drives()
+            .into_iter()
+            .filter_map(|(letter, target)| {
+                let target = fs::canonicalize(&target).unwrap_or(target);
+                let rest = path.strip_prefix(&target).ok()?.to_owned();
+                Some((target.components().count(), letter, rest))
+            })
+            .max_by_key(|(depth, _, _)| *depth)
+            .map(|(_, letter, rest)| (letter, rest))?;
+
+        let mut windows_path = format!("{}:", letter);
+        for part in rest.iter() {
+            windows_path.push('\\');
+            windows_path.push_str(&part.to_string_lossy());
+        }
+        if rest.as_os_str().is_empty() {
+            windows_path.push('\\');
+        }
+        Some(windows_path)
+    }
+}
+
+/// Joins `part` onto `dir`, using the existing entry whose name matches ignoring case if there's no exact match
+fn join_ignoring_case(dir: &Path, part: &str) -> PathBuf {
+    let exact = dir.join(part);
+    if exact.exists() {
+        return exact;
+    }
+
+    fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .find(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| name.eq_ignore_ascii_case(part))
+        })
+        .map(|entry| entry.path())
+        .unwrap_or(exact)
+}
+
+/// Removes `.` and `..` from a path without touching the filesystem
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::tests::TempDir;
+    use std::os::unix::fs::symlink;
+
+    #[test]
+    fn translate_paths() {
+        let root = TempDir::new("protonprefix");
+        let prefix = ProtonPrefix::new(root.join("pfx"));
+        let games = prefix.user_dir().join("Documents/My Games");
+        let data = root.join("data");
+        fs::create_dir_all(&games).unwrap();
+        fs::create_dir_all(prefix.path.join("dosdevices")).unwrap();
+        fs::create_dir_all(&data).unwrap();
+        symlink("../drive_c", prefix.path.join("dosdevices/c:")).unwrap();
+        symlink(&data, prefix.path.join("dosdevices/d:")).unwrap();
+        symlink("/", prefix.path.join("dosdevices/z:")).unwrap();
+        symlink("/dev/null", prefix.path.join("dosdevices/com1")).unwrap();
+
+        assert_eq!(
+            prefix.to_unix("c:\\USERS\\steamuser\\documents\\My Games\\"),
+            Some(games.clone())
+        );
+        assert_eq!(
+            prefix.to_unix("D:/saves/../config.ini"),
+            Some(data.join("config.ini"))
+        );
+        assert_eq!(
+            prefix.to_unix("Z:\\usr\\bin"),
+            Some(PathBuf::from("/usr/bin"))
+        );
+        assert_eq!(
+            prefix.to_unix("C:\\..\\..\\..\\etc"),
+            Some(prefix.drive_c().join("etc"))
+        );
+        assert_eq!(
+            prefix.to_unix("d:\\saves\\..\\..\\config.ini"),
+            Some(data.join("config.ini"))
+        );
+        assert_eq!(prefix.to_unix("Q:\\"), None);
+        assert_eq!(prefix.to_unix("\\\\server\\share"), None);
+
+        assert_eq!(
+            prefix.to_windows(&games).as_deref(),
+            Some("C:\\users\\steamuser\\Documents\\My Games")
+        );
+        assert_eq!(
+            prefix.to_windows(&data.join("config.ini")).as_deref(),
+            Some("D:\\config.ini")
+        );
+        assert_eq!(prefix.to_windows(&data).as_deref(), Some("D:\\"));
+        assert_eq!(
+            prefix.to_windows(Path::new("/usr/bin")).as_deref(),
+            Some("Z:\\usr\\bin")
+        );
+
+        // Older prefixes use the Windows XP layout
+        assert_eq!(
+            prefix.known_folder(KnownFolder::Documents),
+            prefix.user_dir().join("Documents")
+        );
+        fs::create_dir_all(prefix.user_dir().join("Application Data")).unwrap();
+        assert_eq!(
+            prefix.known_folder(KnownFolder::AppDataRoaming),
+            prefix.user_dir().join("Application Data")
+        );
+        assert_eq!(
+            prefix.known_folder(KnownFolder::ProgramFilesX86),
+            prefix.drive_c().join("Program Files (x86)")
+        );
+    }
+}
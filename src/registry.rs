@@ -0,0 +1,746 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::error::{Error, Position, Result};
+use crate::write;
+
+/// The registry files of a [ProtonPrefix](struct.ProtonPrefix.html), see [ProtonPrefix::registry](struct.ProtonPrefix.html#method.registry).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum RegistryHive {
+    /// `system.reg`: `HKEY_LOCAL_MACHINE`.
+    System,
+    /// `user.reg`: `HKEY_CURRENT_USER`.
+    User,
+    /// `userdef.reg`: The defaults that new users start with, `HKEY_USERS\.Default`.
+    UserDefault,
+}
+
+impl RegistryHive {
+    /// The name of the file within the prefix.
+    pub fn file_name(self) -> &'static str {
+        match self {
+            RegistryHive::System => "system.reg",
+            RegistryHive::User => "user.reg",
+            RegistryHive::UserDefault => "userdef.reg",
+        }
+    }
+}
+
+/// A Wine registry file, such as the `system.reg` of a Proton prefix, loaded so that its keys can be read and changed without running Wine.
+///
+/// Changes are only written to disk by [`save`](#method.save). Wine loads the registry when the prefix starts and writes it back when the prefix shuts down, so the app shouldn't be running while saving.
+///
+/// Lines that aren't changed are kept exactly as they are, including comments and value types that this doesn't understand, so an unmodified file is saved byte for byte the same.
+///
+/// # Example
+/// ```rust,no_run
+/// # use steamlocate::{RegistryHive, RegistryValue, SteamDir};
+/// let mut steamdir = SteamDir::locate().unwrap();
+/// let prefix = steamdir.compat_data(4000).unwrap().prefix();
+///
+/// let mut registry = prefix.registry(RegistryHive::User).unwrap();
+/// println!("{:?}", registry.value("Software\\Wine\\Direct3D", "renderer"));
+/// registry.set_value(
+///     "Software\\Wine\\Direct3D",
+///     "renderer",
+///     RegistryValue::String("vulkan".to_string()),
+/// );
+/// registry.save().unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct RegistryFile {
+    path: PathBuf,
+    /// The lines before the first key, starting with `WINE REGISTRY Version 2`
+    header: Vec<String>,
+    keys: Vec<RegistryKey>,
+}
+
+/// A key of a [RegistryFile](struct.RegistryFile.html), along with its values.
+#[derive(Clone, Debug)]
+pub struct RegistryKey {
+    name: String,
+    modified: Option<u64>,
+    /// The `[name] modified` line as it was read, until the key is changed
+    raw_header: Option<String>,
+    lines: Vec<Line>,
+}
+
+#[derive(Clone, Debug)]
+enum Line {
+    /// `#time=<hex>`
+    Time(u64),
+    Value {
+        name: String,
+        value: RegistryValue,
+        /// The value's lines as they were read, until the value is changed
+        raw: Option<String>,
+    },
+    /// Blank lines, comments, metadata like `#class` and values that can't be parsed
+    Other(String),
+}
+
+/// The data of a registry value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RegistryValue {
+    /// `"..."`: A `REG_SZ` string.
+    String(String),
+    /// `str(2):"..."`: A `REG_EXPAND_SZ` string, which may refer to environment variables like `%SystemRoot%`.
+    ExpandString(String),
+    /// `str(<kind>):"..."`: A string stored as another type of value.
+    Str { kind: u32, value: String },
+    /// `dword:<hex>`: A `REG_DWORD`.
+    Dword(u32),
+    /// `hex:<bytes>`: A `REG_BINARY`.
+    Binary(Vec<u8>),
+    /// `hex(<kind>):<bytes>`: Any other type of value, e.g. `hex(7)` for a `REG_MULTI_SZ` or `hex(b)` for a `REG_QWORD`.
+    Hex { kind: u32, data: Vec<u8> },
+}
+
+impl RegistryValue {
+    /// Returns the string of a `String`, `ExpandString` or `Str`.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            RegistryValue::String(value)
+            | RegistryValue::ExpandString(value)
+            | RegistryValue::Str { value, .. } => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of a `Dword`.
+    pub fn as_dword(&self) -> Option<u32> {
+        match self {
+            RegistryValue::Dword(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the bytes of a `Binary` or `Hex`.
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            RegistryValue::Binary(data) | RegistryValue::Hex { data, .. } => Some(data),
+            _ => None,
+        }
+    }
+}
+
+impl RegistryFile {
+    /// Reads the registry file at `path`.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path).map_err(|err| Error::io(err, path))?;
+        if !text.starts_with("WINE REGISTRY Version ") {
+            return Err(Error::parse(
+                path,
+                Some(Position::LineColumn { line: 1, column: 1 }),
+                "expected a `WINE REGISTRY Version` header",
+            ));
+        }
+
+        let mut file = RegistryFile {
+            path: path.to_owned(),
+            header: Vec::new(),
+            keys: Vec::new(),
+        };
+        let mut lines = text.split('\n').enumerate();
+        while let Some((index, line)) = lines.next() {
+            if line.starts_with('[') {
+                let (name, modified) = parse_key_header(line).ok_or_else(|| {
+                    Error::parse(
+                        path,
+                        Some(Position::LineColumn {
+                            line: index + 1,
+                            column: 1,
+                        }),
+                        "expected a key like `[Software\\\\Wine] 1700000000`",
+                    )
+                })?;
+                file.keys.push(RegistryKey {
+                    name,
+                    modified,
+                    raw_header: Some(line.to_string()),
+                    lines: Vec::new(),
+                });
+                continue;
+            }
+
+            let key = match file.keys.last_mut() {
+                Some(key) => key,
+                None => {
+                    file.header.push(line.to_string());
+                    continue;
+                }
+            };
+
+            if let Some(time) = line
+                .strip_prefix("#time=")
+                .and_then(|time| u64::from_str_radix(time, 16).ok())
+            {
+                key.lines.push(Line::Time(time));
+            } else if line.starts_with('"') || line.starts_with('@') {
+                // Long binary values are continued on the following lines
+                let mut raw = line.to_string();
+                while raw.ends_with('\\') {
+                    match lines.next() {
+                        Some((_, next)) => {
+                            raw.push('\n');
+                            raw.push_str(next);
+                        }
+                        None => break,
+                    }
+                }
+                key.lines.push(match parse_value_line(&raw) {
+                    Some((name, value)) => Line::Value {
+                        name,
+                        value,
+                        raw: Some(raw),
+                    },
+                    None => Line::Other(raw),
+                });
+            } else {
+                key.lines.push(Line::Other(line.to_string()));
+            }
+        }
+
+        Ok(file)
+    }
+
+    /// The path that the file was loaded from, and that [`save`](#method.save) writes to.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The key that every key in the file is relative to, e.g. `\Machine` for `system.reg`.
+    pub fn relative_to(&self) -> Option<String> {
+        self.header.iter().find_map(|line| {
+            let root = line.strip_prefix(";; All keys relative to ")?;
+            Some(unescape(root.trim_end()))
+        })
+    }
+
+    /// The architecture of the prefix, e.g. `win64`.
+    pub fn arch(&self) -> Option<&str> {
+        self.header
+            .iter()
+            .find_map(|line| line.strip_prefix("#arch="))
+            .map(|arch| arch.trim_end())
+    }
+
+    /// Returns every key in the file, in the order that they're written in.
+    pub fn keys(&self) -> &[RegistryKey] {
+        &self.keys
+    }
+
+    /// Returns the key with the given path, such as `Software\Wine\Direct3D`, ignoring case.
+    pub fn key(&self, path: &str) -> Option<&RegistryKey> {
+        self.position(path).map(|index| &self.keys[index])
+    }
+
+    /// Returns the value with the given name within the key with the given path. The default value of a key is named `""`.
+    pub fn value(&self, key: &str, name: &str) -> Option<&RegistryValue> {
+        self.key(key)?.value(name)
+    }
+
+    /// Sets a value, creating the key if it doesn't exist yet.
+    ///
+    /// A value that already exists is replaced in place, while a new one is added after the key's other values. The key's modification time is updated just like Wine does.
+    pub fn set_value(&mut self, key: &str, name: &str, value: RegistryValue) {
+        let index = match self.position(key) {
+            Some(index) => index,
+            None => {
+                self.keys.push(RegistryKey {
+                    name: key.trim_matches('\\').to_string(),
+                    modified: None,
+                    raw_header: None,
+                    lines: vec![Line::Time(0), Line::Other(String::new())],
+                });
+                self.keys.len() - 1
+            }
+        };
+        let key = &mut self.keys[index];
+        key.touch();
+
+        let existing = key.lines.iter_mut().find_map(|line| match line {
+            Line::Value {
+                name: existing,
+                value,
+                raw,
+            } if existing.eq_ignore_ascii_case(name) => Some((value, raw)),
+            _ => None,
+        });
+        if let Some((existing, raw)) = existing {
+            *existing = value;
+            *raw = None;
+            return;
+        }
+
+        let position = key
+            .lines
+            .iter()
+            .rposition(|line| !matches!(line, Line::Other(other) if other.trim().is_empty()))
+            .map_or(0, |last| last + 1);
+        key.lines.insert(
+            position,
+            Line::Value {
+                name: name.to_string(),
+                value,
+                raw: None,
+            },
+        );
+    }
+
+    /// Removes a value, returning it. The key itself is kept, even if it has no values left.
+    pub fn remove_value(&mut self, key: &str, name: &str) -> Option<RegistryValue> {
+        let index = self.position(key)?;
+        let key = &mut self.keys[index];
+        let line = key.lines.iter().position(|line| match line {
+            Line::Value { name: existing, .. } => existing.eq_ignore_ascii_case(name),
+            _ => false,
+        })?;
+        key.touch();
+        match key.lines.remove(line) {
+            Line::Value { value, .. } => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Writes the registry back to [`path`](#method.path).
+    ///
+    /// The file is replaced like every other file that this crate edits, such as by [SteamDir::set_launch_options](struct.SteamDir.html#method.set_launch_options), so the previous version is kept next to it, e.g. as `user.reg.bak`.
+    pub fn save(&self) -> Result<()> {
+        write::replace_with_backup(&self.path, self.to_text().as_bytes())
+    }
+
+    fn to_text(&self) -> String {
+        let mut lines = self.header.clone();
+        for key in &self.keys {
+            lines.push(match &key.raw_header {
+                Some(raw) => raw.clone(),
+                None => match key.modified {
+                    Some(modified) => format!("[{}] {}", escape(&key.name, ']'), modified),
+                    None => format!("[{}]", escape(&key.name, ']')),
+                },
+            });
+            for line in &key.lines {
+                lines.push(match line {
+                    Line::Time(time) => format!("#time={:x}", time),
+                    Line::Value { raw: Some(raw), .. } => raw.clone(),
+                    Line::Value {
+                        name,
+                        value,
+                        raw: None,
+                    } => render_value(name, value),
+                    Line::Other(other) => other.clone(),
+                });
+            }
+        }
+        lines.join("\n")
+    }
+
+    fn position(&self, path: &str) -> Option<usize> {
+        let path = path.trim_matches('\\');
+        self.keys
+            .iter()
+            .position(|key| key.name.eq_ignore_ascii_case(path))
+    }
+}
+
+impl RegistryKey {
+    /// The path of the key relative to [RegistryFile::relative_to](struct.RegistryFile.html#method.relative_to), e.g. `Software\Wine\Direct3D`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The Unix timestamp of the last time the key was changed, written after the key's name.
+    pub fn modified(&self) -> Option<u64> {
+        self.modified
+    }
+
+    /// `#time`: The last time the key was changed as a Windows `FILETIME`, in 100 nanosecond intervals since 1601.
+    pub fn time(&self) -> Option<u64> {
+        self.lines.iter().find_map(|line| match line {
+            Line::Time(time) => Some(*time),
+            _ => None,
+        })
+    }
+
+    /// Returns the value with the given name, ignoring case. The default value of the key is named `""`.
+    pub fn value(&self, name: &str) -> Option<&RegistryValue> {
+        self.values()
+            .find(|(existing, _)| existing.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value)
+    }
+
+    /// Returns the names and data of the key's values, in the order that they're written in.
+    pub fn values(&self) -> impl Iterator<Item = (&str, &RegistryValue)> {
+        self.lines.iter().filter_map(|line| match line {
+            Line::Value { name, value, .. } => Some((name.as_str(), value)),
+            _ => None,
+        })
+    }
+
+    /// Updates the modification times to now
+    fn touch(&mut self) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        // The number of 100 nanosecond intervals between 1601 and 1970
+        const UNIX_EPOCH_AS_FILETIME: u64 = 116_444_736_000_000_000;
+        let time =
+            UNIX_EPOCH_AS_FILETIME + now.as_secs() * 10_000_000 + now.subsec_nanos() as u64 / 100;
+
+        self.modified = Some(now.as_secs());
+        self.raw_header = None;
+        for line in &mut self.lines {
+            if let Line::Time(existing) = line {
+                *existing = time;
+            }
+        }
+    }
+}
+
+/// Parses `[name] modified`, where the modification time is optional
+fn parse_key_header(line: &str) -> Option<(String, Option<u64>)> {
+    let (name, rest) = split_quoted(&line[1..], ']')?;
+    Some((unescape(name), rest.trim().parse().ok()))
+}
+
+/// Parses `"name"=data` or `@=data`
+fn parse_value_line(raw: &str) -> Option<(String, RegistryValue)> {
+    let (name, data) = match raw.strip_prefix('@') {
+        Some(data) => (String::new(), data),
+        None => {
+            let (name, data) = split_quoted(&raw[1..], '"')?;
+            (unescape(name), data)
+        }
+    };
+    let data = data.strip_prefix('=')?;
+
+    let string = |data: &str| -> Option<String> {
+        let (value, rest) = split_quoted(data.strip_prefix('"')?, '"')?;
+        Some(unescape(value)).filter(|_| rest.trim().is_empty())
+    };
+
+    let value = if data.starts_with('"') {
+        RegistryValue::String(string(data)?)
+    } else if let Some(data) = data.strip_prefix("str(") {
+        match parse_kind(data)? {
+            (2, value) => RegistryValue::ExpandString(string(value)?),
+            (kind, value) => RegistryValue::Str {
+                kind,
+                value: string(value)?,
+            },
+        }
+    } else if let Some(dword) = data.strip_prefix("dword:") {
+        RegistryValue::Dword(u32::from_str_radix(dword.trim(), 16).ok()?)
+    } else if let Some(bytes) = data.strip_prefix("hex:") {
+        RegistryValue::Binary(parse_hex(bytes)?)
+    } else if let Some(data) = data.strip_prefix("hex(") {
+        let (kind, bytes) = parse_kind(data)?;
+        RegistryValue::Hex {
+            kind,
+            data: parse_hex(bytes)?,
+        }
+    } else {
+        return None;
+    };
+    Some((name, value))
+}
+
+/// Parses the `<kind>):<rest>` that follows `str(` or `hex(`
+fn parse_kind(data: &str) -> Option<(u32, &str)> {
+    let (kind, rest) = data.split_once("):")?;
+    Some((u32::from_str_radix(kind, 16).ok()?, rest))
+}
+
+/// Parses comma-separated bytes, which may be continued on following lines with a trailing `\`
+fn parse_hex(bytes: &str) -> Option<Vec<u8>> {
+    bytes
+        .split(',')
+        .map(|byte| byte.trim_matches(|c: char| c == '\\' || c.is_ascii_whitespace()))
+        .filter(|byte| !byte.is_empty())
+        .map(|byte| u8::from_str_radix(byte, 16).ok())
+        .collect()
+}
+
+/// Splits `text` at the first `delimiter` that isn't escaped, returning the text before and after it
+fn split_quoted(text: &str, delimiter: char) -> Option<(&str, &str)> {
+    let mut escaped = false;
+    for (index, c) in text.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            c if c == delimiter => return Some((&text[..index], &text[index + 1..])),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Undoes Wine's escaping, which has C escapes, octal escapes and `\x` followed by a UTF-16 code unit in hex
+fn unescape(text: &str) -> String {
+    let mut units = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buffer = [0; 2];
+            units.extend_from_slice(c.encode_utf16(&mut buffer));
+            continue;
+        }
+
+        let unit = match chars.next() {
+            Some('a') => 0x07,
+            Some('b') => 0x08,
+            Some('e') => 0x1b,
+            Some('f') => 0x0c,
+            Some('n') => 0x0a,
+            Some('r') => 0x0d,
+            Some('t') => 0x09,
+            Some('v') => 0x0b,
+            Some('x') => take_digits(&mut chars, 16, 4, 0),
+            Some(digit @ '0'..='7') => take_digits(&mut chars, 8, 2, digit as u16 - '0' as u16),
+            Some(other) => other as u16,
+            None => '\\' as u16,
+        };
+        units.push(unit);
+    }
+    String::from_utf16_lossy(&units)
+}
+
+/// Reads up to `max` more digits onto `unit`
+fn take_digits(
+    chars: &mut std::iter::Peekable<std::str::Chars>,
+    radix: u32,
+    max: usize,
+    mut unit: u16,
+) -> u16 {
+    for _ in 0..max {
+        match chars.peek().and_then(|c| c.to_digit(radix)) {
+            Some(digit) => {
+                unit = unit.wrapping_mul(radix as u16).wrapping_add(digit as u16);
+                chars.next();
+            }
+            None => break,
+        }
+    }
+    unit
+}
+
+/// Escapes text the way Wine does, with `delimiter` being the character that ends it
+fn escape(text: &str, delimiter: char) -> String {
+    const ESCAPES: &[u8; 32] = b".......abtnvfr.............e....";
+    let units: Vec<u16> = text.encode_utf16().collect();
+    let mut escaped = String::new();
+    for (index, &unit) in units.iter().enumerate() {
+        let next = units.get(index + 1).copied().unwrap_or(0);
+        if unit > 127 {
+            // A hex digit right after the escape would be read as part of it
+            if next < 128 && (next as u8).is_ascii_hexdigit() {
+                escaped += &format!("\\x{:04x}", unit);
+            } else {
+                escaped += &format!("\\x{:x}", unit);
+            }
+        } else if unit < 32 {
+            match ESCAPES[unit as usize] {
+                b'.' if (u16::from(b'0')..=u16::from(b'7')).contains(&next) => {
+                    escaped += &format!("\\{:03o}", unit)
+                }
+                b'.' => escaped += &format!("\\{:o}", unit),
+                c => {
+                    escaped.push('\\');
+                    escaped.push(c as char);
+                }
+            }
+        } else {
+            let c = unit as u8 as char;
+            if c == '\\' || c == delimiter {
+                escaped.push('\\');
+            }
+            escaped.push(c);
+        }
+    }
+    escaped
+}
+
+fn render_value(name: &str, value: &RegistryValue) -> String {
+    let mut line = if name.is_empty() {
+        "@=".to_string()
+    } else {
+        format!("\"{}\"=", escape(name, '"'))
+    };
+
+    let bytes = match value {
+        RegistryValue::String(value) => return line + &format!("\"{}\"", escape(value, '"')),
+        RegistryValue::ExpandString(value) => {
+            return line + &format!("str(2):\"{}\"", escape(value, '"'))
+        }
+        RegistryValue::Str { kind, value } => {
+            return line + &format!("str({:x}):\"{}\"", kind, escape(value, '"'))
+        }
+        RegistryValue::Dword(value) => return line + &format!("dword:{:08x}", value),
+        RegistryValue::Binary(data) => {
+            line += "hex:";
+            data
+        }
+        RegistryValue::Hex { kind, data } => {
+            line += &format!("hex({:x}):", kind);
+            data
+        }
+    };
+
+    // Wine wraps long values after the comma that takes a line past 76 characters
+    let mut column = line.len();
+    for (index, byte) in bytes.iter().enumerate() {
+        line += &format!("{:02x}", byte);
+        column += 2;
+        if index + 1 < bytes.len() {
+            line.push(',');
+            column += 1;
+            if column > 76 {
+                line += "\\\n  ";
+                column = 2;
+            }
+        }
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::TempDir;
+
+    const GMOD: &str = "Software\\Wow6432Node\\Facepunch\\Garry's Mod";
+
+    /// Copies the sample `system.reg` into `dir`
+    fn sample_copy(dir: &TempDir) -> PathBuf {
+        let path = dir.join("system.reg");
+        fs::copy(
+            Path::new(env!("CARGO_MANIFEST_DIR")).join(
+                "tests/sample_data/home/.steam/steam/steamapps/compatdata/4000/pfx/system.reg",
+            ),
+            &path,
+        )
+        .unwrap();
+        path
+    }
+
+    #[test]
+    fn unmodified_round_trip() {
+        let dir = TempDir::new("registry");
+        let path = sample_copy(&dir);
+        let original = fs::read_to_string(&path).unwrap();
+        let registry = RegistryFile::load(&path).unwrap();
+        assert_eq!(registry.to_text(), original);
+
+        registry.save().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), original);
+        assert_eq!(
+            fs::read_to_string(path.with_file_name("system.reg.bak")).unwrap(),
+            original
+        );
+    }
+
+    #[test]
+    fn set_and_remove() {
+        let dir = TempDir::new("registry");
+        let path = sample_copy(&dir);
+        let original = fs::read_to_string(&path).unwrap();
+        let mut registry = RegistryFile::load(&path).unwrap();
+
+        // Re-rendering a value gives the same text that Wine writes
+        let signature = registry.value(GMOD, "Signature").unwrap().clone();
+        registry.set_value(GMOD, "signature", signature);
+        let owner = registry.value(GMOD, "Owner").unwrap().clone();
+        registry.set_value(GMOD, "Owner", owner);
+        let text = registry.to_text();
+        let unchanged = |text: &str| {
+            text.lines()
+                .filter(|line| !line.starts_with("[Software\\\\Wow") && !line.starts_with("#time"))
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        assert_eq!(unchanged(&text), unchanged(&original));
+        let key = registry.key(GMOD).unwrap();
+        assert!(key.modified().unwrap() > 1699999000);
+        assert!(key.time().unwrap() > 0x1da1b2c3d4e5f60);
+
+        let odd = "tab\there\n\u{7}é 中1 \u{1F600} \\ \"quoted\" ]";
+        registry.set_value(GMOD, "Odd", RegistryValue::String(odd.to_string()));
+        registry.set_value(GMOD, "Version", RegistryValue::Dword(7));
+        registry.set_value(
+            "Software\\Valve\\Steam\\Apps\\4000\\",
+            "",
+            RegistryValue::Hex {
+                kind: 0xb,
+                data: vec![1, 0, 0, 0, 0, 0, 0, 0],
+            },
+        );
+        assert_eq!(
+            registry.remove_value("software\\valve\\steam", "LANGUAGE"),
+            Some(RegistryValue::String("english".to_string()))
+        );
+        assert_eq!(registry.remove_value(GMOD, "Language"), None);
+        registry.save().unwrap();
+
+        let registry = RegistryFile::load(&path).unwrap();
+        assert_eq!(registry.value(GMOD, "odd").unwrap().as_str(), Some(odd));
+        assert_eq!(registry.value(GMOD, "Version").unwrap().as_dword(), Some(7));
+        let apps = registry.key("Software\\Valve\\Steam\\Apps\\4000").unwrap();
+        assert_eq!(
+            apps.value("").unwrap().as_bytes(),
+            Some(&[1, 0, 0, 0, 0, 0, 0, 0][..])
+        );
+        assert!(apps.time().is_some());
+        assert_eq!(registry.value("Software\\Valve\\Steam", "Language"), None);
+        let names: Vec<_> = registry
+            .key(GMOD)
+            .unwrap()
+            .values()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                "",
+                "InstallDir",
+                "Owner",
+                "Path",
+                "Version",
+                "Signature",
+                "Mods",
+                "Odd"
+            ]
+        );
+
+        let text = fs::read_to_string(&path).unwrap();
+        assert!(text.contains(concat!(
+            "\"Broken\"=something(9):unknown\n",
+            r#""Odd"="tab\there\n\a\xe9 \x4e2d1 \xd83d\xde00 \\ \"quoted\" ]""#,
+            "\n\n[System"
+        )));
+    }
+
+    #[test]
+    fn errors() {
+        let dir = TempDir::new("registry");
+        let path = sample_copy(&dir);
+        fs::write(&path, "REGEDIT4\n").unwrap();
+        assert!(matches!(
+            RegistryFile::load(&path),
+            Err(Error::Parse { .. })
+        ));
+        fs::write(&path, "WINE REGISTRY Version 2\n\n[Software\\\\Wine 1\n").unwrap();
+        assert!(matches!(
+            RegistryFile::load(&path),
+            Err(Error::Parse {
+                position: Some(Position::LineColumn { line: 3, .. }),
+                ..
+            })
+        ));
+    }
+}
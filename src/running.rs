@@ -0,0 +1,43 @@
+//! Checking whether Steam is running, since it overwrites the files it has loaded when it exits
+
+/// Looks for a process named `steam`
+#[cfg(target_os = "linux")]
+pub(crate) fn is_steam_running() -> bool {
+    let processes = match std::fs::read_dir("/proc") {
+        Ok(processes) => processes,
+        Err(_) => return false,
+    };
+
+    processes.filter_map(|entry| entry.ok()).any(|entry| {
+        let is_pid = entry
+            .file_name()
+            .to_str()
+            .is_some_and(|name| name.bytes().all(|b| b.is_ascii_digit()));
+        is_pid
+            && std::fs::read_to_string(entry.path().join("comm"))
+                .is_ok_and(|comm| comm.trim_end() == "steam")
+    })
+}
+
+/// Asks `pgrep` for a process named `steam_osx`
+#[cfg(target_os = "macos")]
+pub(crate) fn is_steam_running() -> bool {
+    std::process::Command::new("pgrep")
+        .args(["-x", "steam_osx"])
+        .output()
+        .is_ok_and(|output| output.status.success())
+}
+
+/// Steam records its process ID in the registry while it runs, and resets it to 0 when it exits
+#[cfg(target_os = "windows")]
+pub(crate) fn is_steam_running() -> bool {
+    use winreg::{
+        enums::{HKEY_CURRENT_USER, KEY_READ},
+        RegKey,
+    };
+
+    RegKey::predef(HKEY_CURRENT_USER)
+        .open_subkey_with_flags("Software\\Valve\\Steam\\ActiveProcess", KEY_READ)
+        .and_then(|key| key.get_value::<u32, _>("pid"))
+        .is_ok_and(|pid| pid != 0)
+}
@@ -1,11 +1,19 @@
-//! **WARN:** This is all hacky and should be replaced with proper binary VDF parsing
+//! Non-Steam games, which are stored in the binary VDF files `userdata/<user_id>/config/shortcuts.vdf`
 
-use std::{fs, iter::Peekable, path::Path, slice::Iter};
+use std::{fs, path::Path};
+
+use crate::artwork::Artwork;
+use crate::binary_vdf::{self, Map, Value};
+use crate::compatdata::{self, CompatData};
+use crate::compatmapping::{AppCompatTool, CompatToolMapping};
+use crate::error::{Error, Result};
+use crate::libraryfolders::LibraryFolders;
+use crate::steamid::SteamId;
 
 /// A added non-Steam game
 ///
 /// Information is parsed from your `userdata/<user_id>/config/shortcuts.vdf` files
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 #[non_exhaustive]
 pub struct Shortcut {
     /// Steam's provided app id
@@ -18,10 +26,112 @@ pub struct Shortcut {
     pub executable: String,
     /// The directory that the application should be run in
     pub start_dir: String,
+    /// The path to the icon shown for the shortcut, or empty to use the executable's icon
+    pub icon: String,
+    /// The path to the desktop entry or shortcut that the shortcut was created from, if any
+    pub shortcut_path: String,
+    /// The options passed to the executable when launching it
+    pub launch_options: String,
+    /// Whether the shortcut is hidden from the library
+    pub is_hidden: bool,
+    /// Whether the desktop controller configuration is used while the app is running
+    pub allow_desktop_config: bool,
+    /// Whether the Steam overlay is enabled for the app
+    pub allow_overlay: bool,
+    /// Whether the shortcut is included in the SteamVR library
+    pub open_vr: bool,
+    /// Whether the shortcut was created by the Steam devkit tools
+    pub devkit: bool,
+    /// The game ID assigned by the Steam devkit tools
+    pub devkit_game_id: String,
+    /// The app ID that the Steam devkit tools run the shortcut as, or `0`
+    pub devkit_override_app_id: u32,
+    /// The Unix timestamp of the last time the shortcut was launched, or `0` if it never has been
+    pub last_play_time: u32,
+    /// The ID of the Flatpak that the shortcut launches, if any
+    pub flatpak_app_id: String,
+    /// The collections that the shortcut has been added to
+    pub tags: Vec<String>,
+    /// The account ID of the Steam user whose `userdata/<account_id>` folder the shortcut belongs to
+    ///
+    /// This isn't stored in `shortcuts.vdf` itself, so it is `None` for shortcuts that weren't loaded from a user's folder
+    pub account_id: Option<u32>,
+}
+
+impl Shortcut {
+    /// The SteamID of the user that the shortcut belongs to, see [`account_id`](#structfield.account_id)
+    pub fn owner(&self) -> Option<SteamId> {
+        self.account_id.map(SteamId::from_account_id)
+    }
+
+    /// The 64-bit game ID that Steam uses for the shortcut, built from its app ID
+    ///
+    /// Unlike [`steam_id`](#method.steam_id), this uses the app ID that Steam actually assigned, so it also works for shortcuts whose app ID wasn't calculated from their executable and name
+    pub fn game_id(&self) -> u64 {
+        ((self.appid as u64) << 32) | 0x02000000
+    }
+
+    /// The URL that launches the shortcut through Steam, e.g. `steam://rungameid/11966957034873552896`
+    pub fn rungameid_url(&self) -> String {
+        format!("steam://rungameid/{}", self.game_id())
+    }
+
+    /// The custom artwork of the shortcut within the Steam installation at `steam_dir`
+    ///
+    /// Returns `None` if the shortcut isn't associated with a user, see [`account_id`](#structfield.account_id)
+    pub fn artwork(&self, steam_dir: &Path) -> Option<Artwork> {
+        Some(Artwork {
+            grid_dir: steam_dir
+                .join("userdata")
+                .join(self.account_id?.to_string())
+                .join("config")
+                .join("grid"),
+            appid: self.appid,
+        })
+    }
+
+    /// The compatibility tool that the shortcut is set to run with, falling back to the default tool, see [CompatToolMapping::get](struct.CompatToolMapping.html#method.get)
+    pub fn compat_tool<'a>(&self, mapping: &'a CompatToolMapping) -> Option<&'a AppCompatTool> {
+        mapping.get(self.appid)
+    }
+
+    /// Finds the shortcut's Proton prefix, which Steam usually creates in the library folder of the Steam installation itself
+    ///
+    /// Returns `None` if the shortcut has never been run through Proton
+    pub fn compat_data_path(&self, libraryfolders: &LibraryFolders) -> Option<CompatData> {
+        compatdata::find(self.appid, None, libraryfolders)
+    }
 }
 
 #[cfg(feature = "shortcuts_extras")]
 impl Shortcut {
+    /// Creates a shortcut with the settings that Steam gives new shortcuts, and an app ID calculated by [`calculate_appid`](#method.calculate_appid)
+    ///
+    /// `executable` and `start_dir` are usually quoted, e.g. `"/usr/bin/retroarch"`
+    pub fn new(
+        app_name: impl Into<String>,
+        executable: impl Into<String>,
+        start_dir: impl Into<String>,
+    ) -> Self {
+        let mut shortcut = Shortcut {
+            app_name: app_name.into(),
+            executable: executable.into(),
+            start_dir: start_dir.into(),
+            allow_desktop_config: true,
+            allow_overlay: true,
+            ..Default::default()
+        };
+        shortcut.appid = shortcut.calculate_appid();
+        shortcut
+    }
+
+    /// Calculates an app ID for the shortcut from the executable and app name, which is the upper half of its [`steam_id`](#method.steam_id)
+    ///
+    /// Steam accepts any app ID with the highest bit set, so this only needs to be unique among a user's shortcuts
+    pub fn calculate_appid(&self) -> u32 {
+        (self.steam_id() >> 32) as u32
+    }
+
     /// Calculates the shortcut's Steam ID from the executable and app name
     pub fn steam_id(&self) -> u64 {
         let algorithm = crc::Crc::<u32>::new(&crc::CRC_32_ISO_HDLC);
@@ -49,7 +159,7 @@ pub fn discover_shortcuts(steam_dir: &Path) -> Vec<Shortcut> {
             }
 
             if let Ok(contents) = fs::read(&shortcuts_path) {
-                if let Some(parsed) = parse_shortcuts(&contents) {
+                if let Ok(parsed) = parse_shortcuts(&contents, &shortcuts_path) {
                     shortcuts.extend(parsed);
                 }
             }
@@ -61,99 +171,111 @@ pub fn discover_shortcuts(steam_dir: &Path) -> Vec<Shortcut> {
     inner(steam_dir).unwrap_or_default()
 }
 
-/// Advances `it` until right after the matching `needle`
+/// Discovers any shortcuts stored within `userdata`, returning the first error encountered
 ///
-/// Only works if the starting byte is not used anywhere else in the needle. This works well when
-/// finding keys since the starting byte indicates the type and wouldn't be used in the key
-#[must_use]
-fn after_many_case_insensitive(it: &mut Peekable<Iter<u8>>, needle: &[u8]) -> bool {
-    loop {
-        loop {
-            let mut needle_it = needle.iter();
-            let b = match it.next() {
-                Some(b) => b,
-                None => return false,
-            };
-
-            let maybe_needle_b = needle_it.next();
-            if maybe_u8_eq_ignore_ascii_case(maybe_needle_b, Some(b)) {
-                loop {
-                    if needle_it.len() == 0 {
-                        return true;
-                    }
-
-                    let maybe_b = it.peek();
-                    let maybe_needle_b = needle_it.next();
-                    if maybe_u8_eq_ignore_ascii_case(maybe_needle_b, maybe_b.copied()) {
-                        let _ = it.next();
-                    } else {
-                        break;
-                    }
-                }
-            }
-        }
-    }
-}
+/// A Steam installation without a `userdata` directory simply has no shortcuts
+pub fn try_discover_shortcuts(steam_dir: &Path) -> Result<Vec<Shortcut>> {
+    let mut shortcuts = Vec::new();
 
-fn maybe_u8_eq_ignore_ascii_case(maybe_b1: Option<&u8>, maybe_b2: Option<&u8>) -> bool {
-    maybe_b1
-        .zip(maybe_b2)
-        .map(|(b1, b2)| b1.eq_ignore_ascii_case(b2))
-        .unwrap_or_default()
-}
+    let user_data = steam_dir.join("userdata");
+    if !user_data.is_dir() {
+        return Ok(shortcuts);
+    }
 
-fn parse_value_str(it: &mut Peekable<Iter<u8>>) -> Option<String> {
-    let mut buff = Vec::new();
-    loop {
-        let b = it.next()?;
-        if *b == 0x00 {
-            break Some(String::from_utf8_lossy(&buff).into_owned());
+    for entry in fs::read_dir(&user_data).map_err(|err| Error::io(err, &user_data))? {
+        let entry = entry.map_err(|err| Error::io(err, &user_data))?;
+        let shortcuts_path = entry.path().join("config").join("shortcuts.vdf");
+        if !shortcuts_path.is_file() {
+            continue;
         }
 
-        buff.push(*b);
+        let contents = fs::read(&shortcuts_path).map_err(|err| Error::io(err, &shortcuts_path))?;
+        shortcuts.extend(parse_shortcuts(&contents, &shortcuts_path)?);
     }
-}
 
-fn parse_value_u32(it: &mut Peekable<Iter<u8>>) -> Option<u32> {
-    let bytes = [*it.next()?, *it.next()?, *it.next()?, *it.next()?];
-    Some(u32::from_le_bytes(bytes))
+    Ok(shortcuts)
 }
 
-// The performance of this is likely terrible, but also the files we're parsing are tiny so it
-// won't matter
-fn parse_shortcuts(contents: &[u8]) -> Option<Vec<Shortcut>> {
-    let mut it = contents.iter().peekable();
-    let mut shortcuts = Vec::new();
-
-    loop {
-        if !after_many_case_insensitive(&mut it, b"\x02appid\x00") {
-            return Some(shortcuts);
-        }
-        let appid = parse_value_u32(&mut it)?;
+/// Parses the contents of a `shortcuts.vdf` file, where `path` is only used to give context to errors
+///
+/// The file has a single `shortcuts` map which holds each shortcut under its index
+fn parse_shortcuts(contents: &[u8], path: &Path) -> Result<Vec<Shortcut>> {
+    let vdf = binary_vdf::parse(contents).map_err(|err| Error::binary_vdf(err, path))?;
+    let entries = vdf
+        .get("shortcuts")
+        .and_then(Value::as_map)
+        .ok_or_else(|| Error::missing_key(path, "shortcuts"))?;
 
-        if !after_many_case_insensitive(&mut it, b"\x01AppName\x00") {
-            return None;
-        }
-        let app_name = parse_value_str(&mut it)?;
+    entries
+        .iter()
+        .map(|(_, entry)| {
+            let entry = entry
+                .as_map()
+                .ok_or_else(|| Error::parse(path, None, "shortcut entry is not a map"))?;
+            parse_shortcut(entry, path)
+        })
+        .collect()
+}
 
-        if !after_many_case_insensitive(&mut it, b"\x01Exe\x00") {
-            return None;
-        }
-        let executable = parse_value_str(&mut it)?;
+/// Gets the account ID out of a `userdata/<account_id>/config/shortcuts.vdf` path
+pub(crate) fn account_id(shortcuts_path: &Path) -> Option<u32> {
+    shortcuts_path
+        .parent()?
+        .parent()?
+        .file_name()?
+        .to_str()?
+        .parse()
+        .ok()
+}
 
-        if !after_many_case_insensitive(&mut it, b"\x01StartDir\x00") {
-            return None;
-        }
-        let start_dir = parse_value_str(&mut it)?;
+/// Only the `appid`, `AppName`, `Exe` and `StartDir` keys are required, the rest are left empty if
+/// they are missing
+pub(crate) fn parse_shortcut(entry: &Map, path: &Path) -> Result<Shortcut> {
+    let string = |key: &'static str| {
+        entry
+            .get(key)
+            .and_then(Value::as_str)
+            .map(|value| value.to_string())
+            .ok_or_else(|| Error::missing_key(path, key))
+    };
+    let optional_string = |key| string(key).unwrap_or_default();
+    let int = |key| entry.get(key).and_then(Value::as_i32).unwrap_or_default();
+    let bool = |key| int(key) != 0;
 
-        let shortcut = Shortcut {
-            appid,
-            app_name,
-            executable,
-            start_dir,
-        };
-        shortcuts.push(shortcut);
-    }
+    Ok(Shortcut {
+        // Stored as a signed integer, but Steam treats it as unsigned
+        appid: entry
+            .get("appid")
+            .and_then(Value::as_i32)
+            .map(|appid| appid as u32)
+            .ok_or_else(|| Error::missing_key(path, "appid"))?,
+        app_name: string("AppName")?,
+        executable: string("Exe")?,
+        start_dir: string("StartDir")?,
+        icon: optional_string("icon"),
+        shortcut_path: optional_string("ShortcutPath"),
+        launch_options: optional_string("LaunchOptions"),
+        is_hidden: bool("IsHidden"),
+        allow_desktop_config: bool("AllowDesktopConfig"),
+        allow_overlay: bool("AllowOverlay"),
+        open_vr: bool("OpenVR"),
+        devkit: bool("Devkit"),
+        devkit_game_id: optional_string("DevkitGameID"),
+        devkit_override_app_id: int("DevkitOverrideAppID") as u32,
+        last_play_time: int("LastPlayTime") as u32,
+        flatpak_app_id: optional_string("FlatpakAppID"),
+        account_id: account_id(path),
+        tags: entry
+            .get("tags")
+            .and_then(Value::as_map)
+            .map(|tags| {
+                tags.iter()
+                    .filter_map(|(_, tag)| tag.as_str())
+                    .map(|tag| tag.to_string())
+                    .collect()
+            })
+            .unwrap_or_default(),
+    })
 }
 
 #[cfg(test)]
@@ -163,7 +285,7 @@ mod tests {
     #[test]
     fn sanity() {
         let contents = include_bytes!("../tests/sample_data/shortcuts.vdf");
-        let shortcuts = parse_shortcuts(contents).unwrap();
+        let shortcuts = parse_shortcuts(contents, Path::new("shortcuts.vdf")).unwrap();
         assert_eq!(
             shortcuts,
             vec![
@@ -172,24 +294,36 @@ mod tests {
                     app_name: "Anki".into(),
                     executable: "\"anki\"".into(),
                     start_dir: "\"./\"".into(),
+                    shortcut_path: "/usr/share/applications/anki.desktop".into(),
+                    allow_desktop_config: true,
+                    allow_overlay: true,
+                    ..Default::default()
                 },
                 Shortcut {
                     appid: 2492174738,
                     app_name: "LibreOffice Calc".into(),
                     executable: "\"libreoffice\"".into(),
                     start_dir: "\"./\"".into(),
+                    shortcut_path: "/usr/share/applications/libreoffice-calc.desktop".into(),
+                    launch_options: "--calc".into(),
+                    allow_desktop_config: true,
+                    allow_overlay: true,
+                    ..Default::default()
                 },
                 Shortcut {
                     appid: 3703025501,
                     app_name: "foo.sh".into(),
                     executable: "\"/usr/local/bin/foo.sh\"".into(),
                     start_dir: "\"/usr/local/bin/\"".into(),
+                    allow_desktop_config: true,
+                    allow_overlay: true,
+                    ..Default::default()
                 }
             ],
         );
 
         let contents = include_bytes!("../tests/sample_data/shortcuts_different_key_case.vdf");
-        let shortcuts = parse_shortcuts(contents).unwrap();
+        let shortcuts = parse_shortcuts(contents, Path::new("shortcuts.vdf")).unwrap();
         assert_eq!(
             shortcuts,
             vec![Shortcut {
@@ -197,18 +331,90 @@ mod tests {
                 app_name: "Second Life".into(),
                 executable: "\"/Applications/Second Life Viewer.app\"".into(),
                 start_dir: "\"/Applications/\"".into(),
+                allow_desktop_config: true,
+                allow_overlay: true,
+                last_play_time: 1667160268,
+                ..Default::default()
+            }]
+        );
+    }
+
+    #[test]
+    fn optional_fields() {
+        let mut contents = Vec::new();
+        contents.extend_from_slice(b"\x00shortcuts\x00\x000\x00");
+        contents.extend_from_slice(b"\x02appid\x00\x01\x00\x00\x80");
+        contents.extend_from_slice(b"\x01AppName\x00Emulator\x00");
+        contents.extend_from_slice(b"\x01Exe\x00/usr/bin/emulator\x00");
+        contents.extend_from_slice(b"\x01StartDir\x00/usr/bin/\x00");
+        contents.extend_from_slice(b"\x01icon\x00/usr/share/icons/emulator.png\x00");
+        contents.extend_from_slice(b"\x02IsHidden\x00\x01\x00\x00\x00");
+        contents.extend_from_slice(b"\x02OpenVR\x00\x01\x00\x00\x00");
+        contents.extend_from_slice(b"\x02Devkit\x00\x01\x00\x00\x00");
+        contents.extend_from_slice(b"\x01DevkitGameID\x00emulator\x00");
+        contents.extend_from_slice(b"\x02DevkitOverrideAppID\x00\x0a\x00\x00\x00");
+        contents.extend_from_slice(b"\x01FlatpakAppID\x00org.example.Emulator\x00");
+        contents.extend_from_slice(b"\x00tags\x00\x010\x00Retro\x00\x011\x00Favorites\x00\x08");
+        contents.extend_from_slice(b"\x08\x08\x08");
+
+        let shortcuts = parse_shortcuts(&contents, Path::new("shortcuts.vdf")).unwrap();
+        assert_eq!(
+            shortcuts,
+            vec![Shortcut {
+                appid: 0x80000001,
+                app_name: "Emulator".into(),
+                executable: "/usr/bin/emulator".into(),
+                start_dir: "/usr/bin/".into(),
+                icon: "/usr/share/icons/emulator.png".into(),
+                is_hidden: true,
+                open_vr: true,
+                devkit: true,
+                devkit_game_id: "emulator".into(),
+                devkit_override_app_id: 10,
+                flatpak_app_id: "org.example.Emulator".into(),
+                tags: vec!["Retro".into(), "Favorites".into()],
+                ..Default::default()
             }]
         );
     }
 
+    #[test]
+    fn launch_url_and_artwork() {
+        let shortcut = Shortcut {
+            appid: 2786274309,
+            account_id: Some(12345678),
+            ..Default::default()
+        };
+        assert_eq!(shortcut.owner().unwrap().steam3(), "[U:1:12345678]");
+        assert_eq!(shortcut.game_id(), 0xa6132c0502000000);
+        assert_eq!(
+            shortcut.rungameid_url(),
+            "steam://rungameid/11966957034873552896"
+        );
+        assert_eq!(
+            shortcut.artwork(Path::new("/steam")).unwrap().grid_dir,
+            Path::new("/steam/userdata/12345678/config/grid")
+        );
+
+        let shortcut = Shortcut {
+            account_id: None,
+            ..shortcut
+        };
+        assert!(shortcut.artwork(Path::new("/steam")).is_none());
+    }
+
     #[cfg(feature = "shortcuts_extras")]
     #[test]
     fn shortcuts_extras() {
         let contents = include_bytes!("../tests/sample_data/shortcuts.vdf");
-        let shortcuts = parse_shortcuts(contents).unwrap();
+        let shortcuts = parse_shortcuts(contents, Path::new("shortcuts.vdf")).unwrap();
         let ideal_ids = vec![0xe89614fe02000000, 0xdb01c79902000000, 0x9d55017302000000];
         for (id, shortcut) in ideal_ids.into_iter().zip(shortcuts.iter()) {
             assert_eq!(id, shortcut.steam_id());
         }
+
+        let anki = Shortcut::new("Anki", "\"anki\"", "\"./\"");
+        assert_eq!(anki.appid, 0xe89614fe);
+        assert!(anki.allow_overlay && anki.allow_desktop_config);
     }
 }
@@ -0,0 +1,291 @@
+//! Editing a user's `shortcuts.vdf`, which is only available with the `shortcuts_extras` feature
+//! since new shortcuts need their app ID calculated
+
+use std::path::{Path, PathBuf};
+
+use crate::binary_vdf::{self, Map, Value};
+use crate::error::{Error, Result};
+use crate::shortcut::{account_id, parse_shortcut, Shortcut};
+use crate::write;
+
+/// A user's `userdata/<user_id>/config/shortcuts.vdf` file, loaded so that non-Steam games can be added, changed and removed.
+///
+/// Changes are only written to disk by [`save`](#method.save). Steam only reads the file when it starts and overwrites it when it exits, so it shouldn't be running while saving.
+///
+/// Keys that [Shortcut](struct.Shortcut.html) doesn't know about are kept as they are, so an unmodified file is saved byte for byte the same.
+///
+/// This requires the `shortcuts_extras` feature.
+///
+/// # Example
+/// ```rust,no_run
+/// # use steamlocate::{Shortcut, ShortcutsFile, SteamDir};
+/// let steamdir = SteamDir::locate().unwrap();
+/// let path = steamdir.path.join("userdata/12345678/config/shortcuts.vdf");
+/// let mut shortcuts = ShortcutsFile::load(&path).unwrap();
+/// shortcuts.add(Shortcut::new("RetroArch", "\"/usr/bin/retroarch\"", "\"/usr/bin/\""));
+/// shortcuts.save().unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct ShortcutsFile {
+    path: PathBuf,
+    root: Map,
+    shortcuts: Vec<Shortcut>,
+    /// The raw entry for each of `shortcuts`, which holds the keys that aren't parsed
+    entries: Vec<Map>,
+}
+
+impl ShortcutsFile {
+    /// Loads the `shortcuts.vdf` file at `path`. A file that doesn't exist yet is treated as having no shortcuts.
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut file = ShortcutsFile {
+            path: path.to_owned(),
+            root: Map::new(),
+            shortcuts: Vec::new(),
+            entries: Vec::new(),
+        };
+        if !path.exists() {
+            return Ok(file);
+        }
+
+        file.root = binary_vdf::load(path)?;
+        let entries = file
+            .root
+            .get("shortcuts")
+            .and_then(Value::as_map)
+            .ok_or_else(|| Error::missing_key(path, "shortcuts"))?;
+        for (_, entry) in entries.iter() {
+            let entry = entry
+                .as_map()
+                .ok_or_else(|| Error::parse(path, None, "shortcut entry is not a map"))?;
+            file.shortcuts.push(parse_shortcut(entry, path)?);
+            file.entries.push(entry.clone());
+        }
+
+        Ok(file)
+    }
+
+    /// The path that the file was loaded from and will be saved to.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The account ID of the user that the file belongs to, taken from its `userdata/<account_id>` folder.
+    pub fn account_id(&self) -> Option<u32> {
+        account_id(&self.path)
+    }
+
+    /// The shortcuts in the order that Steam stores them.
+    pub fn shortcuts(&self) -> &[Shortcut] {
+        &self.shortcuts
+    }
+
+    /// Returns the shortcut with the given app ID.
+    pub fn get(&self, appid: u32) -> Option<&Shortcut> {
+        self.shortcuts
+            .iter()
+            .find(|shortcut| shortcut.appid == appid)
+    }
+
+    /// Adds a shortcut, returning its app ID.
+    ///
+    /// If the shortcut's `appid` is `0`, it is calculated with [Shortcut::calculate_appid](struct.Shortcut.html#method.calculate_appid). A shortcut that already has the same app ID is replaced.
+    ///
+    /// The shortcut's `account_id` is set to the file's.
+    pub fn add(&mut self, mut shortcut: Shortcut) -> u32 {
+        if shortcut.appid == 0 {
+            shortcut.appid = shortcut.calculate_appid();
+        }
+        shortcut.account_id = self.account_id();
+        let appid = shortcut.appid;
+
+        if !self.update(shortcut.clone()) {
+            self.shortcuts.push(shortcut);
+            self.entries.push(Map::new());
+        }
+
+        appid
+    }
+
+    /// Replaces the shortcut that has the same app ID, keeping any keys that [Shortcut](struct.Shortcut.html) doesn't know about.
+    ///
+    /// Returns `false` if there is no such shortcut.
+    pub fn update(&mut self, shortcut: Shortcut) -> bool {
+        match self.position(shortcut.appid) {
+            Some(index) => {
+                self.shortcuts[index] = Shortcut {
+                    account_id: self.account_id(),
+                    ..shortcut
+                };
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes the shortcut with the given app ID, returning it.
+    pub fn remove(&mut self, appid: u32) -> Option<Shortcut> {
+        let index = self.position(appid)?;
+        self.entries.remove(index);
+        Some(self.shortcuts.remove(index))
+    }
+
+    /// Writes the shortcuts back to [`path`](#method.path).
+    ///
+    /// The file is written to a temporary file first and then renamed over the original, so it is never left half-written. The previous file is kept next to it with a `.bak` extension.
+    pub fn save(&self) -> Result<()> {
+        let mut entries = Map::new();
+        for (index, (shortcut, entry)) in self.shortcuts.iter().zip(&self.entries).enumerate() {
+            let mut entry = entry.clone();
+            write_shortcut(shortcut, &mut entry);
+            entries.insert(index.to_string(), Value::Map(entry));
+        }
+        let mut root = self.root.clone();
+        root.insert("shortcuts", Value::Map(entries));
+        write::replace_with_backup(&self.path, &binary_vdf::to_bytes(&root))
+    }
+
+    fn position(&self, appid: u32) -> Option<usize> {
+        self.shortcuts
+            .iter()
+            .position(|shortcut| shortcut.appid == appid)
+    }
+}
+
+/// Writes the fields of `shortcut` into `entry`
+///
+/// Keys that are already in `entry` keep their position and casing, and new ones are added in the
+/// order that Steam writes them
+fn write_shortcut(shortcut: &Shortcut, entry: &mut Map) {
+    let string = |value: &str| Value::String(value.to_string());
+    let bool = |value: bool| Value::Int32(value as i32);
+
+    entry.insert("appid", Value::Int32(shortcut.appid as i32));
+    entry.insert("AppName", string(&shortcut.app_name));
+    entry.insert("Exe", string(&shortcut.executable));
+    entry.insert("StartDir", string(&shortcut.start_dir));
+    entry.insert("icon", string(&shortcut.icon));
+    entry.insert("ShortcutPath", string(&shortcut.shortcut_path));
+    entry.insert("LaunchOptions", string(&shortcut.launch_options));
+    entry.insert("IsHidden", bool(shortcut.is_hidden));
+    entry.insert("AllowDesktopConfig", bool(shortcut.allow_desktop_config));
+    entry.insert("AllowOverlay", bool(shortcut.allow_overlay));
+    entry.insert("OpenVR", bool(shortcut.open_vr));
+    entry.insert("Devkit", bool(shortcut.devkit));
+    entry.insert("DevkitGameID", string(&shortcut.devkit_game_id));
+    entry.insert(
+        "DevkitOverrideAppID",
+        Value::Int32(shortcut.devkit_override_app_id as i32),
+    );
+    entry.insert("LastPlayTime", Value::Int32(shortcut.last_play_time as i32));
+    entry.insert("FlatpakAppID", string(&shortcut.flatpak_app_id));
+
+    let mut tags = Map::new();
+    for (index, tag) in shortcut.tags.iter().enumerate() {
+        tags.insert(index.to_string(), string(tag));
+    }
+    entry.insert("tags", Value::Map(tags));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::TempDir;
+    use std::fs;
+
+    /// Copies one of the sample `shortcuts.vdf` files into `dir`
+    fn sample_copy(dir: &TempDir, sample: &str) -> PathBuf {
+        let path = dir.join("shortcuts.vdf");
+        fs::copy(
+            Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("tests/sample_data")
+                .join(sample),
+            &path,
+        )
+        .unwrap();
+        path
+    }
+
+    #[test]
+    fn unmodified_round_trip() {
+        for sample in &["shortcuts.vdf", "shortcuts_different_key_case.vdf"] {
+            let dir = TempDir::new("shortcuts-round-trip");
+            let path = sample_copy(&dir, sample);
+            let original = fs::read(&path).unwrap();
+
+            ShortcutsFile::load(&path).unwrap().save().unwrap();
+            assert_eq!(fs::read(&path).unwrap(), original);
+            assert_eq!(fs::read(path.with_extension("vdf.bak")).unwrap(), original);
+        }
+    }
+
+    #[test]
+    fn add_update_remove() {
+        let dir = TempDir::new("shortcuts-edit");
+        let path = sample_copy(&dir, "shortcuts.vdf");
+        let mut file = ShortcutsFile::load(&path).unwrap();
+
+        let retroarch = Shortcut::new("RetroArch", "\"/usr/bin/retroarch\"", "\"/usr/bin/\"");
+        let appid = file.add(retroarch.clone());
+        assert_eq!(appid, retroarch.calculate_appid());
+        assert!(appid & 0x80000000 != 0);
+
+        let mut calc = file.get(2492174738).unwrap().clone();
+        calc.launch_options = "--calc --nologo".into();
+        calc.tags = vec!["Office".into()];
+        assert!(file.update(calc.clone()));
+        assert_eq!(file.remove(2786274309).unwrap().app_name, "Anki");
+        assert!(!file.update(Shortcut::default()));
+        file.save().unwrap();
+
+        let shortcuts = ShortcutsFile::load(&path).unwrap();
+        let names: Vec<_> = shortcuts
+            .shortcuts()
+            .iter()
+            .map(|shortcut| shortcut.app_name.as_str())
+            .collect();
+        assert_eq!(names, vec!["LibreOffice Calc", "foo.sh", "RetroArch"]);
+        assert_eq!(shortcuts.get(2492174738), Some(&calc));
+        assert_eq!(shortcuts.get(appid), Some(&retroarch));
+
+        // New shortcuts are written the way Steam writes them
+        let vdf = binary_vdf::load(&path).unwrap();
+        let entries = vdf.get("shortcuts").and_then(Value::as_map).unwrap();
+        let keys: Vec<_> = entries.iter().map(|(key, _)| key).collect();
+        assert_eq!(keys, vec!["0", "1", "2"]);
+        let new_keys: Vec<_> = entries
+            .get("2")
+            .and_then(Value::as_map)
+            .unwrap()
+            .iter()
+            .map(|(key, _)| key)
+            .collect();
+        let anki = binary_vdf::parse(include_bytes!("../tests/sample_data/shortcuts.vdf")).unwrap();
+        let steam_keys: Vec<_> = anki
+            .get("shortcuts")
+            .and_then(Value::as_map)
+            .and_then(|entries| entries.get("0"))
+            .and_then(Value::as_map)
+            .unwrap()
+            .iter()
+            .map(|(key, _)| key)
+            .collect();
+        assert_eq!(new_keys, steam_keys);
+    }
+
+    #[test]
+    fn missing_file() {
+        let dir = TempDir::new("shortcuts-missing");
+        let path = dir.join("config/shortcuts.vdf");
+        let mut file = ShortcutsFile::load(&path).unwrap();
+        assert!(file.shortcuts().is_empty());
+
+        file.add(Shortcut::new(
+            "foo.sh",
+            "\"/usr/local/bin/foo.sh\"",
+            "\"/usr/local/bin/\"",
+        ));
+        file.save().unwrap();
+        assert!(!path.with_extension("vdf.bak").exists());
+        assert_eq!(ShortcutsFile::load(&path).unwrap().shortcuts().len(), 1);
+    }
+}
@@ -1,5 +1,14 @@
 use std::path::{Path, PathBuf};
 
+use crate::appmanifest::AppManifest;
+use crate::appstate::AppState;
+use crate::compatdata::{self, CompatData};
+use crate::compatmapping::{AppCompatTool, CompatToolMapping};
+use crate::error::{Error, Result};
+use crate::libraryfolders::LibraryFolders;
+use crate::localconfig::{AppLocalConfig, UserLocalConfig};
+use crate::steamid::SteamId;
+
 /// An instance of an installed Steam app.
 /// # Example
 /// ```rust
@@ -13,8 +22,9 @@ use std::path::{Path, PathBuf};
 ///     appid: u32: 4000,
 ///     path: PathBuf: "C:\\Program Files (x86)\\steamapps\\common\\GarrysMod",
 ///     vdf: <steamy_vdf::Table>,
+///     manifest: AppManifest { .. },
 ///     name: Some(String: "Garry's Mod"),
-///     last_user: Some(u64: 76561198040894045) // This will be a steamid_ng::SteamID if the "steamid_ng" feature is enabled
+///     last_user: Some(SteamId(76561198040894045))
 /// )
 /// ```
 #[derive(Debug, Clone)]
@@ -30,56 +40,171 @@ pub struct SteamApp {
     /// A [steamy_vdf::Table](https://docs.rs/steamy-vdf/*/steamy_vdf/struct.Table.html)
     pub vdf: steamy_vdf::Table,
 
+    /// The typed contents of the app's manifest.
+    pub manifest: AppManifest,
+
+    /// The install state of the app, decoded from the manifest's `StateFlags`.
+    pub state: AppState,
+
     /// The store name of the Steam app.
     pub name: Option<String>,
 
-    #[cfg(not(feature = "steamid_ng"))]
-    /// The SteamID64 of the last Steam user that played this game on the filesystem.
+    /// The SteamID of the last Steam user that played this game on the filesystem.
     ///
-    /// This crate supports [steamid-ng](https://docs.rs/steamid-ng) and can automatically convert this to a [SteamID](https://docs.rs/steamid-ng/*/steamid_ng/struct.SteamID.html) for you.
-    ///
-    /// To enable this support, [use the  `steamid_ng` Cargo.toml feature](https://docs.rs/steamlocate/*/steamlocate#using-steamlocate).
-    pub last_user: Option<u64>,
-
-    #[cfg(feature = "steamid_ng")]
-    /// The [SteamID](https://docs.rs/steamid-ng/*/steamid_ng/struct.SteamID.html) of the last Steam user that played this game on the filesystem.
-    pub last_user: Option<steamid_ng::SteamID>,
+    /// With the [`steamid_ng` Cargo.toml feature](https://docs.rs/steamlocate/*/steamlocate#using-steamlocate), this can be converted to a [steamid_ng::SteamID](https://docs.rs/steamid-ng/*/steamid_ng/struct.SteamID.html) with `From`.
+    pub last_user: Option<SteamId>,
 }
 
 impl SteamApp {
-    pub(crate) fn new(steamapps: &Path, vdf: &steamy_vdf::Table) -> Option<SteamApp> {
-        // First check if the installation path exists and is a valid directory
-        let install_dir = steamapps.join(vdf.get("installdir")?.as_str()?);
-        if !install_dir.is_dir() {
-            return None;
-        }
+    /// Doesn't check that the install directory exists, see `check_install_dir`
+    ///
+    /// `manifest_path` is only used to give context to errors
+    pub(crate) fn new(
+        steamapps: &Path,
+        vdf: &steamy_vdf::Table,
+        manifest_path: &Path,
+    ) -> Result<SteamApp> {
+        // Get the appid key, try and parse it as an unsigned 32-bit integer
+        let appid = vdf
+            .get("appid")
+            .and_then(|entry| entry.as_value())
+            .and_then(|val| val.parse::<u32>().ok())
+            .ok_or_else(|| Error::missing_key(manifest_path, "appid"))?;
+
+        let install_dir = steamapps.join(
+            vdf.get("installdir")
+                .and_then(|entry| entry.as_str())
+                .ok_or_else(|| Error::missing_key(manifest_path, "installdir"))?,
+        );
 
-        Some(SteamApp {
+        let manifest = AppManifest::from_table(vdf);
+
+        Ok(SteamApp {
             vdf: vdf.clone(),
+            state: AppState::from_bits_retain(manifest.state_flags.unwrap_or_default()),
+            manifest,
             path: install_dir,
-
-            // Get the appid key, try and parse it as an unsigned 32-bit integer, if we fail, return None
-            appid: vdf.get("appid")?.as_value()?.parse::<u32>().ok()?,
+            appid,
 
             // Get the name key, try and convert it into a String, if we fail, name = None
             name: vdf
                 .get("name")
                 .and_then(|entry| entry.as_str().map(|str| str.to_string())),
 
-            // Get the LastOwner key, try and convert it into a SteamID64, if we fail, last_user = None
-            #[cfg(not(feature = "steamid_ng"))]
+            // Get the LastOwner key, try and convert it into a SteamID, if we fail, last_user = None
             last_user: vdf
                 .get("LastOwner")
-                .and_then(|entry| entry.as_value().and_then(|val| val.parse::<u64>().ok())),
-
-            #[cfg(feature = "steamid_ng")]
-            last_user: vdf.get("LastOwner").and_then(|entry| {
-                entry.as_value().and_then(|val| {
-                    val.parse::<u64>()
-                        .ok()
-                        .and_then(|steamid64| Some(steamid_ng::SteamID::from(steamid64)))
-                })
-            }),
+                .and_then(|entry| entry.as_value().and_then(|val| val.parse().ok())),
         })
     }
+
+    /// Checks that the installation path exists and is a valid directory
+    pub(crate) fn check_install_dir(&self) -> Result<()> {
+        if self.path.is_dir() {
+            Ok(())
+        } else {
+            Err(Error::InvalidInstallDir {
+                app_id: self.appid,
+                path: self.path.clone(),
+            })
+        }
+    }
+
+    /// Returns `true` if the app is fully installed and doesn't need an update, so it can be launched.
+    pub fn is_fully_installed(&self) -> bool {
+        self.state.contains(AppState::FULLY_INSTALLED) && !self.needs_update()
+    }
+
+    /// Returns `true` if an update is required before the app can be launched.
+    pub fn needs_update(&self) -> bool {
+        self.state.contains(AppState::UPDATE_REQUIRED)
+    }
+
+    /// Returns `true` if Steam is currently running an update, whether or not it is paused.
+    pub fn is_updating(&self) -> bool {
+        self.state.contains(AppState::UPDATE_RUNNING)
+    }
+
+    /// Returns `true` if Steam is downloading the app's files.
+    pub fn is_downloading(&self) -> bool {
+        self.state.contains(AppState::DOWNLOADING)
+    }
+
+    /// Returns `true` if Steam is verifying the integrity of the app's files.
+    pub fn is_validating(&self) -> bool {
+        self.state.contains(AppState::VALIDATING)
+    }
+
+    /// Returns `true` if Steam is staging or committing downloaded files.
+    pub fn is_staging(&self) -> bool {
+        self.state
+            .intersects(AppState::STAGING | AppState::COMMITTING)
+    }
+
+    /// Returns `true` if Steam is uninstalling the app.
+    pub fn is_uninstalling(&self) -> bool {
+        self.state.contains(AppState::UNINSTALLING)
+    }
+
+    /// Returns the app's settings and usage from a user's [UserLocalConfig](struct.UserLocalConfig.html).
+    pub fn local_config<'a>(
+        &self,
+        local_config: &'a UserLocalConfig,
+    ) -> Option<&'a AppLocalConfig> {
+        local_config.app(self.appid)
+    }
+
+    /// Returns the compatibility tool that the app is set to run with, falling back to the default tool, see [CompatToolMapping::get](struct.CompatToolMapping.html#method.get).
+    pub fn compat_tool<'a>(&self, mapping: &'a CompatToolMapping) -> Option<&'a AppCompatTool> {
+        mapping.get(self.appid)
+    }
+
+    /// Finds the app's Proton prefix, looking in the library folder that the app is installed in before the others.
+    ///
+    /// Returns `None` if the app has never been run through Proton.
+    pub fn compat_data_path(&self, libraryfolders: &LibraryFolders) -> Option<CompatData> {
+        // `path` is `<library>/steamapps/common/<installdir>`
+        let steamapps = self.path.parent().and_then(Path::parent);
+        compatdata::find(self.appid, steamapps, libraryfolders)
+    }
+
+    /// Returns `true` if Steam found the app's files to be missing or corrupt.
+    pub fn has_error(&self) -> bool {
+        self.state
+            .intersects(AppState::FILES_MISSING | AppState::FILES_CORRUPT)
+    }
+}
+
+/// Whether an app found in a library folder can be used, and if not, why.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum AppStatus {
+    /// The manifest was loaded and the install directory exists.
+    Installed,
+    /// The manifest was loaded, but the install directory doesn't exist. This is usually caused by an interrupted download or by the files being deleted outside of Steam.
+    InstallDirMissing,
+    /// The manifest exists, but it couldn't be read or is missing required keys. [SteamDir::try_app](struct.SteamDir.html#method.try_app) reports the exact error.
+    ManifestUnparseable,
+    /// The library folder couldn't be read, for example because it is on a drive that isn't mounted. The app is only known from the library's entry in `libraryfolders.vdf`.
+    LibraryOffline,
+}
+
+/// An app found in a library folder, whether or not it is actually installed.
+///
+/// See [SteamDir::app_entries](struct.SteamDir.html#method.app_entries).
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct AppEntry {
+    /// The app ID of the app.
+    pub app_id: u32,
+    /// The `steamapps` folder of the library that the app belongs to.
+    pub library: PathBuf,
+    /// The path to the app's manifest. This may not exist if the library is offline.
+    pub manifest_path: PathBuf,
+    /// Whether the app can be used.
+    pub status: AppStatus,
+    /// The app itself, which is available when its manifest could be loaded.
+    pub app: Option<SteamApp>,
+    /// Whether this is the manifest that [SteamDir::apps](struct.SteamDir.html#method.apps) and [SteamDir::app](struct.SteamDir.html#method.app) use for the app, see [SteamDir::app_candidates](struct.SteamDir.html#method.app_candidates).
+    pub is_canonical: bool,
 }
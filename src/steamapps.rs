@@ -1,61 +1,37 @@
+use crate::error::{Error, Result};
+use crate::keyvalues;
 use crate::libraryfolders::LibraryFolders;
-use crate::steamapp::SteamApp;
-use std::collections::HashMap;
+use crate::steamapp::{AppEntry, AppStatus, SteamApp};
+use keyvalues_parser::Vdf;
+use std::{
+    collections::HashMap,
+    fs::DirEntry,
+    path::{Path, PathBuf},
+};
 
 #[derive(Default, Clone, Debug)]
 pub(crate) struct SteamApps {
     pub(crate) apps: HashMap<u32, Option<SteamApp>>,
     pub(crate) discovered: bool,
+    pub(crate) entries: Option<Vec<AppEntry>>,
 }
 
 impl SteamApps {
     pub(crate) fn discover_apps(&mut self, libraryfolders: &LibraryFolders) {
-        self.apps.drain();
+        if self.entries.is_none() {
+            self.discover_entries(libraryfolders);
+        }
+        self.apps = apps_from_entries(self.entries.as_deref().unwrap_or_default());
+        self.discovered = true;
+    }
 
-        for libraryfolder in &libraryfolders.paths {
-            let read_dir = libraryfolder.read_dir();
-            if read_dir.is_err() {
-                continue;
-            }
-            for result in read_dir.unwrap() {
-                let file = match result {
-                    Err(_) => continue,
-                    Ok(file) => file,
-                };
-
-                let mut path = file.path();
-                if !path.is_file() {
-                    continue;
-                }
+    pub(crate) fn try_discover_apps(&mut self, libraryfolders: &LibraryFolders) -> Result<()> {
+        let entries = scan_entries(libraryfolders, true)?;
+        self.apps = apps_from_entries(&entries);
+        self.entries = Some(entries);
+        self.discovered = true;
 
-                let app_id: u32 = match file
-                    .file_name()
-                    .to_str()
-                    .and_then(|name| name.strip_prefix("appmanifest_"))
-                    .and_then(|prefixless_name| prefixless_name.strip_suffix(".acf"))
-                    .and_then(|app_id_str| app_id_str.parse().ok())
-                {
-                    Some(app_id) => app_id,
-                    None => continue,
-                };
-
-                let vdf = match steamy_vdf::load(&path) {
-                    Err(_) => continue,
-                    Ok(vdf) => match vdf.get("AppState") {
-                        None => continue,
-                        Some(app_state) => match app_state.as_table() {
-                            None => continue,
-                            Some(table) => table.to_owned(),
-                        },
-                    },
-                };
-
-                path.pop();
-                path.push("common");
-
-                self.apps.insert(app_id, SteamApp::new(&path, &vdf));
-            }
-        }
+        Ok(())
     }
 
     pub(crate) fn discover_app(
@@ -63,26 +39,229 @@ impl SteamApps {
         libraryfolders: &LibraryFolders,
         app_id: &u32,
     ) -> Option<()> {
-        for libraryfolder in &libraryfolders.paths {
-            let mut appmanifest_path = libraryfolder.join(format!("appmanifest_{}.acf", app_id));
-            if appmanifest_path.is_file() {
-                let appmanifest_vdf = steamy_vdf::load(&appmanifest_path).ok()?;
-
-                appmanifest_path.pop();
-                appmanifest_path.push("common");
-
-                self.apps.insert(
-                    *app_id,
-                    SteamApp::new(
-                        &appmanifest_path,
-                        appmanifest_vdf.get("AppState")?.as_table()?,
-                    ),
-                );
-
-                return Some(());
+        let app = self.try_discover_app(libraryfolders, app_id).ok()??;
+        self.apps.insert(*app_id, Some(app));
+        Some(())
+    }
+
+    /// Lists every app manifest in every library folder, along with the apps that libraries which
+    /// can't be read are known to contain
+    pub(crate) fn discover_entries(&mut self, libraryfolders: &LibraryFolders) {
+        self.entries = scan_entries(libraryfolders, false).ok();
+    }
+
+    /// Returns `Ok(None)` if no library folder contains a manifest for `app_id`
+    ///
+    /// When several library folders do, the canonical one is used (see `mark_canonical`). A manifest
+    /// that can't be loaded is only reported if there is no other one to fall back to
+    pub(crate) fn try_discover_app(
+        &self,
+        libraryfolders: &LibraryFolders,
+        app_id: &u32,
+    ) -> Result<Option<SteamApp>> {
+        let mut candidates = Vec::new();
+        let mut first_error = None;
+
+        for folder in &libraryfolders.folders {
+            let steamapps = folder.steamapps();
+            let manifest_path = steamapps.join(format!("appmanifest_{}.acf", app_id));
+            if !manifest_path.is_file() {
+                continue;
+            }
+
+            match load_manifest(&steamapps, &manifest_path) {
+                Ok(app) => candidates.push(AppEntry {
+                    app_id: *app_id,
+                    library: steamapps,
+                    manifest_path,
+                    status: install_status(&app),
+                    app: Some(app),
+                    is_canonical: false,
+                }),
+                Err(err) => {
+                    first_error.get_or_insert(err);
+                }
+            }
+        }
+
+        mark_canonical(&mut candidates, libraryfolders);
+        match candidates.into_iter().find(|entry| entry.is_canonical) {
+            Some(entry) => {
+                let app = entry.app.unwrap();
+                app.check_install_dir()?;
+                Ok(Some(app))
+            }
+            None => first_error.map_or(Ok(None), Err),
+        }
+    }
+}
+
+/// Scans every library folder for app manifests
+///
+/// When `strict` is set, the first error encountered is returned instead of the offending library
+/// folder or manifest being recorded as offline or unparseable
+fn scan_entries(libraryfolders: &LibraryFolders, strict: bool) -> Result<Vec<AppEntry>> {
+    let mut entries = Vec::new();
+
+    for folder in &libraryfolders.folders {
+        let steamapps = folder.steamapps();
+        let read_dir = match steamapps.read_dir() {
+            Ok(read_dir) => read_dir,
+            Err(err) if strict => return Err(Error::io(err, &steamapps)),
+            Err(_) => {
+                let mut app_ids: Vec<_> = folder.apps.keys().copied().collect();
+                app_ids.sort_unstable();
+                entries.extend(app_ids.into_iter().map(|app_id| AppEntry {
+                    app_id,
+                    library: steamapps.clone(),
+                    manifest_path: steamapps.join(format!("appmanifest_{}.acf", app_id)),
+                    status: AppStatus::LibraryOffline,
+                    app: None,
+                    is_canonical: false,
+                }));
+                continue;
+            }
+        };
+
+        let mut manifests: Vec<(u32, PathBuf)> = Vec::new();
+        for result in read_dir {
+            let file = match result {
+                Ok(file) => file,
+                Err(err) if strict => return Err(Error::io(err, &steamapps)),
+                Err(_) => continue,
+            };
+            if let Some(app_id) = manifest_app_id(&file) {
+                let path = file.path();
+                if path.is_file() {
+                    manifests.push((app_id, path));
+                }
             }
         }
+        manifests.sort_unstable();
+
+        for (app_id, manifest_path) in manifests {
+            let (status, app) = match load_manifest(&steamapps, &manifest_path) {
+                Ok(app) => (install_status(&app), Some(app)),
+                Err(err) if strict => return Err(err),
+                Err(_) => (AppStatus::ManifestUnparseable, None),
+            };
+
+            entries.push(AppEntry {
+                app_id,
+                library: steamapps.clone(),
+                manifest_path,
+                status,
+                app,
+                is_canonical: false,
+            });
+        }
+    }
 
-        None
+    mark_canonical(&mut entries, libraryfolders);
+
+    Ok(entries)
+}
+
+/// Builds the map returned by `SteamDir::apps` out of the canonical entries
+///
+/// Apps whose install directory is missing are recorded as `None`
+fn apps_from_entries(entries: &[AppEntry]) -> HashMap<u32, Option<SteamApp>> {
+    entries
+        .iter()
+        .filter(|entry| entry.is_canonical)
+        .map(|entry| {
+            let app = match entry.status {
+                AppStatus::Installed => entry.app.clone(),
+                _ => None,
+            };
+            (entry.app_id, app)
+        })
+        .collect()
+}
+
+/// Marks the entry that Steam most likely uses for each app ID
+///
+/// When the same app has a manifest in several library folders (e.g. after a library was copied by
+/// hand), the one that is preferred is the one that
+///
+/// 1. has its install directory
+/// 2. is in a library folder whose `apps` in `libraryfolders.vdf` list the app
+/// 3. was updated last according to the manifest's `LastUpdated`
+/// 4. is in the library folder that comes first
+///
+/// `entries` has to be in library folder order for the last rule to hold. Entries without a loaded
+/// manifest are never canonical
+fn mark_canonical(entries: &mut [AppEntry], libraryfolders: &LibraryFolders) {
+    let mut canonical: HashMap<u32, usize> = HashMap::new();
+
+    for (index, entry) in entries.iter().enumerate() {
+        if entry.app.is_none() {
+            continue;
+        }
+        let is_preferred = match canonical.get(&entry.app_id) {
+            Some(&best) => {
+                preference(entry, libraryfolders) > preference(&entries[best], libraryfolders)
+            }
+            None => true,
+        };
+        if is_preferred {
+            canonical.insert(entry.app_id, index);
+        }
     }
+
+    for &index in canonical.values() {
+        entries[index].is_canonical = true;
+    }
+}
+
+/// Sort key for the rules in `mark_canonical`, where greater is better
+fn preference(entry: &AppEntry, libraryfolders: &LibraryFolders) -> (bool, bool, u64) {
+    let is_listed = libraryfolders.folders.iter().any(|folder| {
+        folder.apps.contains_key(&entry.app_id) && folder.steamapps() == entry.library
+    });
+    let last_updated = entry
+        .app
+        .as_ref()
+        .and_then(|app| app.manifest.last_updated)
+        .unwrap_or_default();
+
+    (
+        entry.status == AppStatus::Installed,
+        is_listed,
+        last_updated,
+    )
+}
+
+fn install_status(app: &SteamApp) -> AppStatus {
+    if app.path.is_dir() {
+        AppStatus::Installed
+    } else {
+        AppStatus::InstallDirMissing
+    }
+}
+
+/// Parses the app ID out of an `appmanifest_<appid>.acf` file name
+fn manifest_app_id(file: &DirEntry) -> Option<u32> {
+    file.file_name()
+        .to_str()
+        .and_then(|name| name.strip_prefix("appmanifest_"))
+        .and_then(|prefixless_name| prefixless_name.strip_suffix(".acf"))
+        .and_then(|app_id_str| app_id_str.parse().ok())
+}
+
+fn load_manifest(libraryfolder: &Path, appmanifest_path: &Path) -> Result<SteamApp> {
+    let vdf_text = keyvalues::read(appmanifest_path)?;
+
+    // `steamy_vdf` panics on some malformed files (e.g. truncated ones), so they're weeded out
+    // with `keyvalues_parser` first, which also reports where the error is
+    Vdf::parse(&vdf_text).map_err(|err| Error::keyvalues(err, appmanifest_path))?;
+    let vdf = steamy_vdf::read(vdf_text.as_bytes())
+        .and_then(|mut reader| steamy_vdf::Table::load(&mut reader))
+        .map_err(|err| Error::steamy_vdf(err, appmanifest_path))?;
+    let app_state = vdf
+        .get("AppState")
+        .and_then(|app_state| app_state.as_table())
+        .ok_or_else(|| Error::missing_key(appmanifest_path, "AppState"))?;
+
+    SteamApp::new(&libraryfolder.join("common"), app_state, appmanifest_path)
 }
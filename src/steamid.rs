@@ -0,0 +1,222 @@
+use std::{fmt, str::FromStr};
+
+/// A Steam account's ID, which can be converted between its different forms.
+///
+/// Steam stores IDs as a SteamID64 in most files, names `userdata` folders after the 32-bit account ID, and shows the Steam2 (`STEAM_1:0:6172839`) and Steam3 (`[U:1:12345678]`) text forms in various places. All of these can be parsed with [`str::parse`](https://doc.rust-lang.org/std/primitive.str.html#method.parse).
+///
+/// With the `steamid_ng` feature enabled, this converts to and from a [steamid_ng::SteamID](https://docs.rs/steamid-ng/*/steamid_ng/struct.SteamID.html) with `From`.
+///
+/// # Example
+/// ```rust
+/// # use steamlocate::SteamId;
+/// let steam_id: SteamId = "[U:1:12345678]".parse().unwrap();
+/// assert_eq!(steam_id.steam_id64(), 76561197972611406);
+/// assert_eq!(steam_id.account_id(), 12345678);
+/// assert_eq!(steam_id.steam2(), "STEAM_1:0:6172839");
+/// assert_eq!(steam_id, SteamId::from_account_id(12345678));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SteamId(u64);
+
+/// The public universe, which every regular account belongs to
+const PUBLIC_UNIVERSE: u64 = 1;
+/// The account type of individual user accounts
+const INDIVIDUAL: u64 = 1;
+/// The instance of individual accounts that log in through the Steam client
+const DESKTOP_INSTANCE: u64 = 1;
+
+impl SteamId {
+    /// Creates a SteamId from its SteamID64 form.
+    pub fn from_steam_id64(steam_id64: u64) -> Self {
+        SteamId(steam_id64)
+    }
+
+    /// Creates the SteamId of an individual account in the public universe from its 32-bit account ID.
+    pub fn from_account_id(account_id: u32) -> Self {
+        SteamId::individual(PUBLIC_UNIVERSE, account_id)
+    }
+
+    fn individual(universe: u64, account_id: u32) -> Self {
+        SteamId(
+            (universe << 56) | (INDIVIDUAL << 52) | (DESKTOP_INSTANCE << 32) | account_id as u64,
+        )
+    }
+
+    /// The SteamID64 form, e.g. `76561197972611406`.
+    pub fn steam_id64(self) -> u64 {
+        self.0
+    }
+
+    /// The 32-bit account ID, which names the account's `userdata` folder.
+    pub fn account_id(self) -> u32 {
+        self.0 as u32
+    }
+
+    /// The universe that the account belongs to, `1` being the public universe.
+    pub fn universe(self) -> u8 {
+        (self.0 >> 56) as u8
+    }
+
+    /// The Steam2 form, e.g. `STEAM_1:0:6172839`.
+    ///
+    /// Some older games show the public universe as `0` instead, which [`from_str`](#method.from_str) accepts as well.
+    pub fn steam2(self) -> String {
+        let account_id = self.account_id();
+        format!(
+            "STEAM_{}:{}:{}",
+            self.universe(),
+            account_id & 1,
+            account_id >> 1
+        )
+    }
+
+    /// The Steam3 form of an individual account, e.g. `[U:1:12345678]`.
+    pub fn steam3(self) -> String {
+        format!("[U:{}:{}]", self.universe(), self.account_id())
+    }
+}
+
+impl From<u64> for SteamId {
+    fn from(steam_id64: u64) -> Self {
+        SteamId::from_steam_id64(steam_id64)
+    }
+}
+
+impl From<SteamId> for u64 {
+    fn from(steam_id: SteamId) -> Self {
+        steam_id.steam_id64()
+    }
+}
+
+#[cfg(feature = "steamid_ng")]
+impl From<steamid_ng::SteamID> for SteamId {
+    fn from(steam_id: steamid_ng::SteamID) -> Self {
+        SteamId(u64::from(steam_id))
+    }
+}
+
+#[cfg(feature = "steamid_ng")]
+impl From<SteamId> for steamid_ng::SteamID {
+    fn from(steam_id: SteamId) -> Self {
+        steamid_ng::SteamID::from(steam_id.0)
+    }
+}
+
+/// Shows the SteamID64 form, which can be parsed back.
+impl fmt::Display for SteamId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Parses the SteamID64 (`76561197972611406`), Steam2 (`STEAM_1:0:6172839`) or Steam3 (`[U:1:12345678]`) form.
+///
+/// The Steam2 and Steam3 forms are only supported for individual accounts.
+impl FromStr for SteamId {
+    type Err = ParseSteamIdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(steam2) = s.strip_prefix("STEAM_") {
+            let mut parts = steam2.split(':').map(|part| part.parse::<u32>().ok());
+            let (universe, low_bit, rest) = match (parts.next(), parts.next(), parts.next()) {
+                (Some(Some(universe)), Some(Some(low_bit)), Some(Some(rest))) => {
+                    (universe, low_bit, rest)
+                }
+                _ => return Err(ParseSteamIdError(())),
+            };
+            if parts.next().is_some() || low_bit > 1 || rest > u32::MAX >> 1 || universe > 255 {
+                return Err(ParseSteamIdError(()));
+            }
+            // Games from before the Orange Box show the public universe as 0
+            let universe = if universe == 0 {
+                PUBLIC_UNIVERSE
+            } else {
+                universe as u64
+            };
+            Ok(SteamId::individual(universe, (rest << 1) | low_bit))
+        } else if let Some(steam3) = s.strip_prefix("[U:").and_then(|s| s.strip_suffix(']')) {
+            let mut parts = steam3.split(':');
+            match (
+                parts
+                    .next()
+                    .and_then(|universe| universe.parse::<u8>().ok()),
+                parts.next().and_then(|id| id.parse::<u32>().ok()),
+                parts.next(),
+            ) {
+                (Some(universe), Some(account_id), None) => {
+                    Ok(SteamId::individual(universe as u64, account_id))
+                }
+                _ => Err(ParseSteamIdError(())),
+            }
+        } else {
+            s.parse()
+                .map(SteamId::from_steam_id64)
+                .map_err(|_| ParseSteamIdError(()))
+        }
+    }
+}
+
+/// The error returned when a [SteamId](struct.SteamId.html) can't be parsed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseSteamIdError(());
+
+impl fmt::Display for ParseSteamIdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid SteamID, expected a SteamID64, Steam2 or Steam3 ID")
+    }
+}
+
+impl std::error::Error for ParseSteamIdError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conversions() {
+        let steam_id = SteamId::from_steam_id64(76561198040894045);
+        assert_eq!(steam_id.account_id(), 80628317);
+        assert_eq!(steam_id.universe(), 1);
+        assert_eq!(steam_id.steam2(), "STEAM_1:1:40314158");
+        assert_eq!(steam_id.steam3(), "[U:1:80628317]");
+        assert_eq!(steam_id.to_string(), "76561198040894045");
+        assert_eq!(SteamId::from_account_id(80628317), steam_id);
+        assert_eq!(u64::from(steam_id), 76561198040894045);
+    }
+
+    #[test]
+    fn parse() {
+        let steam_id = SteamId::from_steam_id64(76561198040894045);
+        for text in &[
+            "76561198040894045",
+            "STEAM_1:1:40314158",
+            "STEAM_0:1:40314158",
+            "[U:1:80628317]",
+        ] {
+            assert_eq!(text.parse(), Ok(steam_id), "{}", text);
+        }
+
+        for text in &[
+            "",
+            "gaben",
+            "STEAM_1:2:40314158",
+            "STEAM_1:1",
+            "STEAM_1:1:40314158:0",
+            "[U:1:80628317",
+            "[G:1:80628317]",
+            "[U:1:80628317:1]",
+            "-1",
+        ] {
+            assert!(text.parse::<SteamId>().is_err(), "{}", text);
+        }
+    }
+
+    #[cfg(feature = "steamid_ng")]
+    #[test]
+    fn steamid_ng() {
+        let steam_id = SteamId::from_steam_id64(76561198040894045);
+        let converted = steamid_ng::SteamID::from(steam_id);
+        assert_eq!(converted.steam3(), steam_id.steam3());
+        assert_eq!(SteamId::from(converted), steam_id);
+    }
+}
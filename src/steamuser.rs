@@ -0,0 +1,122 @@
+use std::path::{Path, PathBuf};
+
+use keyvalues_parser::{Obj, Vdf};
+
+use crate::error::{Error, Result};
+use crate::keyvalues;
+use crate::steamid::SteamId;
+
+/// A Steam account that has logged in on this computer, as recorded in `config/loginusers.vdf`.
+///
+/// # Example
+/// ```rust,no_run
+/// # use steamlocate::SteamDir;
+/// let mut steamdir = SteamDir::locate().unwrap();
+/// if let Some(user) = steamdir.most_recent_user() {
+///     println!("{} ({})", user.persona_name, user.account_name);
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SteamUser {
+    /// The SteamID of the account.
+    pub steam_id: SteamId,
+    /// `AccountName`: The name that the user logs in with.
+    pub account_name: String,
+    /// `PersonaName`: The name that is shown to other users.
+    pub persona_name: String,
+    /// `RememberPassword`: Whether the user chose to stay logged in.
+    pub remember_password: bool,
+    /// `MostRecent`: Whether this is the user that logged in last.
+    pub most_recent: bool,
+    /// `Timestamp`: The Unix timestamp of the last time the user logged in.
+    pub timestamp: Option<u64>,
+    /// `WantsOfflineMode`: Whether Steam should start in offline mode for the user.
+    pub wants_offline_mode: bool,
+    /// `AllowAutoLogin`: Whether the user is logged in automatically when Steam starts.
+    pub allow_auto_login: bool,
+    /// The user's `userdata/<account_id>` folder, if it exists.
+    pub userdata: Option<PathBuf>,
+}
+
+impl SteamUser {
+    /// The 32-bit account ID, which names the user's `userdata` folder.
+    pub fn account_id(&self) -> u32 {
+        self.steam_id.account_id()
+    }
+
+    fn from_obj(steam_dir: &Path, steam_id: SteamId, obj: &Obj) -> Self {
+        let string = |key| {
+            keyvalues::get_str(obj, key)
+                .map(|value| value.to_string())
+                .unwrap_or_default()
+        };
+        let bool = |key| keyvalues::get_str(obj, key) == Some("1");
+
+        let userdata = steam_dir
+            .join("userdata")
+            .join(steam_id.account_id().to_string());
+
+        SteamUser {
+            steam_id,
+            account_name: string("AccountName"),
+            persona_name: string("PersonaName"),
+            remember_password: bool("RememberPassword"),
+            most_recent: bool("MostRecent"),
+            timestamp: keyvalues::get_str(obj, "Timestamp").and_then(|time| time.parse().ok()),
+            wants_offline_mode: bool("WantsOfflineMode"),
+            allow_auto_login: bool("AllowAutoLogin"),
+            userdata: Some(userdata).filter(|userdata| userdata.is_dir()),
+        }
+    }
+}
+
+/// Reads the users from `config/loginusers.vdf`, which looks like
+///
+/// ```vdf
+/// "users"
+/// {
+///     "76561197960287930"
+///     {
+///         "AccountName"    "gaben"
+///         "PersonaName"    "Rabscuttle"
+///         ...
+///     }
+/// }
+/// ```
+///
+/// A Steam installation that no one has logged in to doesn't have the file, and so has no users
+pub(crate) fn discover_users(steam_dir: &Path) -> Result<Vec<SteamUser>> {
+    let loginusers_path = steam_dir.join("config").join("loginusers.vdf");
+    if !loginusers_path.is_file() {
+        return Ok(Vec::new());
+    }
+
+    let vdf_text = keyvalues::read(&loginusers_path)?;
+    let value = Vdf::parse(&vdf_text)
+        .map_err(|err| Error::keyvalues(err, &loginusers_path))?
+        .value;
+    let obj = value
+        .get_obj()
+        .ok_or_else(|| Error::unsupported_layout(&loginusers_path, "expected an object"))?;
+
+    let mut users: Vec<_> = obj
+        .iter()
+        .filter_map(|(steam_id, values)| {
+            let steam_id = steam_id.parse().ok()?;
+            let obj = values.first()?.get_obj()?;
+            Some(SteamUser::from_obj(steam_dir, steam_id, obj))
+        })
+        .collect();
+    users.sort_by_key(|user| user.steam_id);
+
+    Ok(users)
+}
+
+/// Picks the user that is marked as `MostRecent`, falling back to the one that logged in last
+pub(crate) fn most_recent(users: &[SteamUser]) -> Option<&SteamUser> {
+    users
+        .iter()
+        .find(|user| user.most_recent)
+        .or_else(|| users.iter().max_by_key(|user| user.timestamp))
+}
@@ -6,6 +6,8 @@
 static APP_ID: u32 = 4000;
 
 use super::*;
+use std::fs;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 #[test]
 fn find_steam() {
@@ -85,3 +87,915 @@ fn all_apps_get_one() {
     assert!(steamapp.unwrap().name.is_some());
     assert!(steamapp.unwrap().last_user.is_some());
 }
+
+// The tests below run against the fake installation in `tests/sample_data/home` and don't require
+// Steam to be installed
+
+fn sample_data() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/sample_data")
+}
+
+fn sample_steam_dir() -> PathBuf {
+    sample_data().join("home/.steam/steam")
+}
+
+#[test]
+fn from_dir() {
+    let mut steamdir = SteamDir::from_dir(sample_steam_dir()).unwrap();
+
+    let steamapp = steamdir.try_app(&APP_ID).unwrap();
+    assert_eq!(steamapp.unwrap().name.as_deref(), Some("Garry's Mod"));
+
+    assert!(matches!(
+        SteamDir::from_dir(sample_data()),
+        Err(Error::UnsupportedLayout { .. })
+    ));
+    assert!(matches!(
+        SteamDir::from_dir(sample_data().join("does_not_exist")),
+        Err(Error::Io { .. })
+    ));
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn locate_in() {
+    let steamdir = SteamDir::locate_in(sample_data().join("home")).unwrap();
+    assert_eq!(steamdir.path, sample_steam_dir());
+
+    assert!(SteamDir::locate_in(sample_data()).is_none());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn locate_all_in() {
+    let home = sample_data().join("home");
+    let steamdirs = SteamDir::locate_all_in(&home);
+
+    // `.steam/root` is a symlink to `.steam/steam`, so it shouldn't be listed separately
+    let found: Vec<_> = steamdirs
+        .iter()
+        .map(|steamdir| (steamdir.kind, steamdir.path.clone()))
+        .collect();
+    assert_eq!(
+        found,
+        vec![
+            (SteamDirKind::Native, home.join(".steam/steam")),
+            (
+                SteamDirKind::Snap,
+                home.join("snap/steam/common/.local/share/Steam")
+            ),
+        ]
+    );
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn try_locate_linux_errors() {
+    let steamdir = try_locate_linux(&sample_data().join("home"), None).unwrap();
+    assert_eq!(steamdir.path, sample_steam_dir());
+
+    let home = TempDir::new("try_locate");
+    assert!(matches!(
+        try_locate_linux(&home, None),
+        Err(Error::FailedLocate)
+    ));
+
+    // The first candidate that exists explains why nothing was found
+    fs::create_dir_all(home.join(".steam")).unwrap();
+    fs::create_dir_all(home.join(".local/share")).unwrap();
+    fs::write(home.join(".local/share/Steam"), "").unwrap();
+    std::os::unix::fs::symlink(home.join("missing"), home.join(".steam/steam")).unwrap();
+    match try_locate_linux(&home, None) {
+        Err(Error::Io { path, .. }) => assert_eq!(path, home.join(".steam/steam")),
+        other => panic!("expected an I/O error, got {:?}", other),
+    }
+    fs::remove_file(home.join(".steam/steam")).unwrap();
+    match try_locate_linux(&home, None) {
+        Err(Error::UnsupportedLayout { path, .. }) => {
+            assert_eq!(path, home.join(".local/share/Steam"))
+        }
+        other => panic!("expected an unsupported layout, got {:?}", other),
+    }
+}
+
+#[test]
+fn locate_from_env_vars() {
+    use std::ffi::OsString;
+
+    let steam_dir = sample_steam_dir();
+    let steam_dir_str = steam_dir.to_str().unwrap().to_owned();
+
+    assert!(locate_from_env(|_| None, &sample_data()).is_none());
+
+    // The explicit override is authoritative, even when it's wrong
+    let found = locate_from_env(
+        |key: &str| match key {
+            "STEAMLOCATE_STEAM_DIR" => Some(OsString::from("/does/not/exist")),
+            "STEAM_COMPAT_CLIENT_INSTALL_PATH" => Some(OsString::from(&steam_dir_str)),
+            _ => None,
+        },
+        &sample_data(),
+    );
+    match found {
+        Some(Err(Error::Io { path, .. })) => assert_eq!(path, Path::new("/does/not/exist")),
+        other => panic!("expected an I/O error, got {:?}", other),
+    }
+    let manifest = steam_dir.join("steamapps/appmanifest_4000.acf");
+    let found = locate_from_env(
+        |key: &str| match key {
+            "STEAMLOCATE_STEAM_DIR" => Some(manifest.clone().into_os_string()),
+            _ => None,
+        },
+        &sample_data(),
+    );
+    assert!(matches!(found, Some(Err(Error::UnsupportedLayout { .. }))));
+
+    let found = locate_from_env(
+        |key: &str| match key {
+            "STEAM_COMPAT_CLIENT_INSTALL_PATH" => Some(OsString::from(&steam_dir_str)),
+            _ => None,
+        },
+        &sample_data(),
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(found.path, steam_dir);
+    assert_eq!(found.kind, SteamDirKind::Environment);
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn locate_from_env_inside_container() {
+    use std::ffi::OsString;
+
+    // Pretend that `tests/sample_data` is where the host's filesystem is mounted
+    let found = locate_from_env(
+        |key: &str| match key {
+            "STEAM_COMPAT_CLIENT_INSTALL_PATH" => Some(OsString::from("/home/.steam/steam")),
+            _ => None,
+        },
+        &sample_data(),
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(found.path, sample_steam_dir());
+}
+
+#[test]
+fn app_state() {
+    let mut steamdir = SteamDir::from_dir(sample_steam_dir()).unwrap();
+    let mut steamapp = steamdir.try_app(&APP_ID).unwrap().unwrap().clone();
+
+    assert_eq!(steamapp.state, AppState::FULLY_INSTALLED);
+    assert!(steamapp.is_fully_installed());
+    assert!(!steamapp.needs_update());
+
+    // An update that is downloading, with an unknown bit set as well
+    steamapp.state = AppState::from_bits_retain(0x8000_0000 | 1_048_838);
+    assert!(steamapp.state.contains(
+        AppState::UPDATE_REQUIRED
+            | AppState::FULLY_INSTALLED
+            | AppState::UPDATE_RUNNING
+            | AppState::DOWNLOADING
+    ));
+    assert_eq!(steamapp.state.bits() & 0x8000_0000, 0x8000_0000);
+    assert!(!steamapp.is_fully_installed());
+    assert!(steamapp.needs_update());
+    assert!(steamapp.is_downloading());
+    assert!(!steamapp.is_validating());
+    assert!(!steamapp.has_error());
+}
+
+/// An empty directory for a test to write files into, such as a fake Steam installation
+///
+/// Every directory is unique so that tests can run in parallel, and it's removed on drop
+pub(crate) struct TempDir(PathBuf);
+
+impl TempDir {
+    pub(crate) fn new(name: &str) -> TempDir {
+        static COUNT: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir().join(format!(
+            "steamlocate-{}-{}-{}",
+            name,
+            std::process::id(),
+            COUNT.fetch_add(1, Ordering::Relaxed)
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        TempDir(dir)
+    }
+}
+
+impl std::ops::Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for TempDir {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Escapes `path` to go within a quoted VDF string, where the backslashes of Windows paths are escapes
+fn vdf_path(path: &Path) -> String {
+    path.display().to_string().replace('\\', "\\\\")
+}
+
+fn write_manifest(steamapps: &Path, app_id: u32, install_dir: &str) {
+    fs::write(
+        steamapps.join(format!("appmanifest_{}.acf", app_id)),
+        format!(
+            "\"AppState\"\n{{\n\t\"appid\"\t\t\"{}\"\n\t\"installdir\"\t\t\"{}\"\n\t\"StateFlags\"\t\t\"4\"\n}}\n",
+            app_id, install_dir
+        ),
+    )
+    .unwrap();
+}
+
+#[test]
+fn app_entries() {
+    let root = TempDir::new("app_entries");
+    let steamapps = root.join("steamapps");
+    for dir in &["steamapps/common/Installed", "config", "userdata"] {
+        fs::create_dir_all(root.join(dir)).unwrap();
+    }
+    write_manifest(&steamapps, 10, "Installed");
+    write_manifest(&steamapps, 20, "Missing");
+    fs::write(steamapps.join("appmanifest_30.acf"), "\"AppState\"\n{\n").unwrap();
+    fs::write(
+        steamapps.join("libraryfolders.vdf"),
+        format!(
+            "\"libraryfolders\"\n{{\n\t\"0\"\n\t{{\n\t\t\"path\"\t\t\"{}\"\n\t}}\n\t\"1\"\n\t{{\n\t\t\"path\"\t\t\"{}\"\n\t\t\"apps\"\n\t\t{{\n\t\t\t\"40\"\t\t\"1024\"\n\t\t}}\n\t}}\n}}\n",
+            vdf_path(&root),
+            vdf_path(&root.join("unmounted"))
+        ),
+    )
+    .unwrap();
+
+    let mut steamdir = SteamDir::from_dir(&root).unwrap();
+    let statuses: Vec<_> = steamdir
+        .app_entries()
+        .iter()
+        .map(|entry| (entry.app_id, entry.status, entry.app.is_some()))
+        .collect();
+    assert_eq!(
+        statuses,
+        vec![
+            (10, AppStatus::Installed, true),
+            (20, AppStatus::InstallDirMissing, true),
+            (30, AppStatus::ManifestUnparseable, false),
+            (40, AppStatus::LibraryOffline, false),
+        ]
+    );
+
+    // The existing APIs still treat these as not installed
+    assert!(steamdir.app(&20).is_none());
+    assert!(matches!(
+        steamdir.try_app(&20),
+        Err(Error::InvalidInstallDir { app_id: 20, .. })
+    ));
+}
+
+#[test]
+fn duplicate_apps() {
+    let root = TempDir::new("duplicate_apps");
+    let library = root.join("library");
+    for dir in &["config", "userdata"] {
+        fs::create_dir_all(root.join(dir)).unwrap();
+    }
+    for steamapps in &[root.join("steamapps"), library.join("steamapps")] {
+        for dir in &["Listed", "Newer", "Installed"] {
+            fs::create_dir_all(steamapps.join("common").join(dir)).unwrap();
+        }
+    }
+    fs::remove_dir(root.join("steamapps/common/Installed")).unwrap();
+
+    let write = |steamapps: &Path, app_id: u32, install_dir: &str, last_updated: u64| {
+        fs::write(
+            steamapps.join(format!("appmanifest_{}.acf", app_id)),
+            format!(
+                "\"AppState\"\n{{\n\t\"appid\"\t\t\"{}\"\n\t\"installdir\"\t\t\"{}\"\n\t\"LastUpdated\"\t\t\"{}\"\n}}\n",
+                app_id, install_dir, last_updated
+            ),
+        )
+        .unwrap();
+    };
+    // Listed in the main library's `apps`, even though the other copy is newer
+    write(&root.join("steamapps"), 50, "Listed", 100);
+    write(&library.join("steamapps"), 50, "Listed", 200);
+    // Neither is listed, so the newer one wins
+    write(&root.join("steamapps"), 60, "Newer", 100);
+    write(&library.join("steamapps"), 60, "Newer", 200);
+    // Only the second library has the install directory
+    write(&root.join("steamapps"), 70, "Installed", 300);
+    write(&library.join("steamapps"), 70, "Installed", 100);
+    // Not a duplicate
+    write(&library.join("steamapps"), 80, "Installed", 100);
+    fs::write(
+        root.join("steamapps/libraryfolders.vdf"),
+        format!(
+            "\"libraryfolders\"\n{{\n\t\"0\"\n\t{{\n\t\t\"path\"\t\t\"{}\"\n\t\t\"apps\"\n\t\t{{\n\t\t\t\"50\"\t\t\"1024\"\n\t\t}}\n\t}}\n\t\"1\"\n\t{{\n\t\t\"path\"\t\t\"{}\"\n\t}}\n}}\n",
+            vdf_path(&root),
+            vdf_path(&library)
+        ),
+    )
+    .unwrap();
+
+    let mut steamdir = SteamDir::from_dir(&root).unwrap();
+
+    let duplicates = steamdir.duplicate_apps();
+    let mut app_ids: Vec<_> = duplicates.keys().copied().collect();
+    app_ids.sort_unstable();
+    assert_eq!(app_ids, vec![50, 60, 70]);
+    for (app_id, canonical_library) in &[(50, &*root), (60, &library), (70, &library)] {
+        let entries = &duplicates[app_id];
+        assert_eq!(entries.len(), 2);
+        assert!(entries[0].is_canonical && !entries[1].is_canonical);
+        assert_eq!(entries[0].library, canonical_library.join("steamapps"));
+    }
+    assert_eq!(steamdir.app_candidates(&80).len(), 1);
+
+    // Every way of getting an app agrees on the canonical one
+    for (app_id, canonical_library) in &[(50, &*root), (60, &library), (70, &library)] {
+        let expected = canonical_library.join("steamapps/common");
+        assert!(steamdir.app(app_id).unwrap().path.starts_with(&expected));
+        let apps = steamdir.try_apps().unwrap();
+        assert!(apps[app_id].as_ref().unwrap().path.starts_with(&expected));
+    }
+}
+
+#[test]
+fn duplicate_apps_in_many_libraries() {
+    let root = TempDir::new("many_libraries");
+    for dir in &["config", "userdata"] {
+        fs::create_dir_all(root.join(dir)).unwrap();
+    }
+    let libraries: Vec<_> = (0..12)
+        .map(|index| match index {
+            0 => root.to_path_buf(),
+            index => root.join(format!("library{}", index)),
+        })
+        .collect();
+    let mut libraryfolders_vdf = String::from("\"libraryfolders\"\n{\n");
+    for (index, library) in libraries.iter().enumerate() {
+        fs::create_dir_all(library.join("steamapps")).unwrap();
+        libraryfolders_vdf.push_str(&format!(
+            "\t\"{}\"\n\t{{\n\t\t\"path\"\t\t\"{}\"\n\t}}\n",
+            index,
+            vdf_path(library)
+        ));
+    }
+    libraryfolders_vdf.push_str("}\n");
+    fs::write(
+        root.join("steamapps/libraryfolders.vdf"),
+        libraryfolders_vdf,
+    )
+    .unwrap();
+
+    // Two identical copies, so the library that Steam lists first wins
+    for library in &[&libraries[10], &libraries[2]] {
+        fs::create_dir_all(library.join("steamapps/common/Game")).unwrap();
+        write_manifest(&library.join("steamapps"), 90, "Game");
+    }
+
+    let mut steamdir = SteamDir::from_dir(&root).unwrap();
+    let libraries: Vec<_> = steamdir
+        .app_candidates(&90)
+        .iter()
+        .map(|entry| (entry.library.clone(), entry.is_canonical))
+        .collect();
+    assert_eq!(
+        libraries,
+        vec![
+            (root.join("library2/steamapps"), true),
+            (root.join("library10/steamapps"), false),
+        ]
+    );
+    assert!(steamdir
+        .app(&90)
+        .unwrap()
+        .path
+        .starts_with(root.join("library2")));
+}
+
+#[test]
+fn shortcuts_for_user() {
+    let mut steamdir = SteamDir::from_dir(sample_steam_dir()).unwrap();
+
+    let names: Vec<_> = steamdir
+        .shortcuts_for_user(12345678)
+        .iter()
+        .map(|shortcut| shortcut.app_name.as_str())
+        .collect();
+    assert_eq!(names, vec!["Anki", "LibreOffice Calc", "foo.sh"]);
+
+    let second_user = steamdir.shortcuts_for_user(87654321);
+    assert_eq!(second_user.len(), 1);
+    assert_eq!(second_user[0].app_name, "Second Life");
+    assert_eq!(second_user[0].account_id, Some(87654321));
+
+    assert!(steamdir.shortcuts_for_user(1).is_empty());
+    assert_eq!(steamdir.try_shortcuts().unwrap().len(), 4);
+}
+
+#[test]
+fn users() {
+    let steam_dir = sample_steam_dir();
+    let mut steamdir = SteamDir::from_dir(&steam_dir).unwrap();
+
+    let users = steamdir.try_users().unwrap();
+    let names: Vec<_> = users
+        .iter()
+        .map(|user| user.account_name.as_str())
+        .collect();
+    assert_eq!(names, vec!["alice", "carol", "bob"]);
+
+    let alice = &users[0];
+    assert_eq!(alice.steam_id, SteamId::from_steam_id64(76561197972611406));
+    assert_eq!(alice.account_id(), 12345678);
+    assert_eq!(alice.persona_name, "Alice");
+    assert!(alice.remember_password && alice.allow_auto_login && !alice.wants_offline_mode);
+    assert_eq!(alice.timestamp, Some(1700000000));
+    assert_eq!(alice.userdata, Some(steam_dir.join("userdata/12345678")));
+
+    let bob = &users[2];
+    assert!(bob.wants_offline_mode && !bob.most_recent);
+    assert_eq!(bob.userdata, Some(steam_dir.join("userdata/87654321")));
+    // Carol has never used this installation
+    assert_eq!(users[1].userdata, None);
+
+    assert_eq!(
+        steamdir
+            .most_recent_user()
+            .map(|user| user.account_name.as_str()),
+        Some("alice")
+    );
+}
+
+#[test]
+fn user_local_config() {
+    let mut steamdir = SteamDir::from_dir(sample_steam_dir()).unwrap();
+    let local_config = steamdir.user_local_config(12345678).unwrap();
+
+    let gmod = steamdir
+        .app(&APP_ID)
+        .unwrap()
+        .local_config(&local_config)
+        .unwrap();
+    assert_eq!(gmod.last_played, Some(1700000000));
+    assert_eq!(gmod.playtime, Some(5234));
+    assert_eq!(gmod.playtime_2wks, Some(95));
+    assert_eq!(gmod.launch_options.as_deref(), Some("-novid -console"));
+    assert_eq!(gmod.cloud_sync_state.as_deref(), Some("synchronized"));
+
+    assert_eq!(local_config.app(7), Some(&AppLocalConfig::default()));
+    let recent: Vec<_> = local_config
+        .recently_played()
+        .into_iter()
+        .map(|(app_id, _)| app_id)
+        .collect();
+    assert_eq!(recent, vec![4000, 2786274309, 440]);
+
+    assert!(matches!(
+        steamdir.user_local_config(87654321),
+        Err(Error::Io { .. })
+    ));
+}
+
+#[test]
+fn write_launch_options() {
+    let dir = TempDir::new("launch-options");
+    let path = dir.join("localconfig.vdf");
+    let original =
+        fs::read_to_string(sample_steam_dir().join("userdata/12345678/config/localconfig.vdf"))
+            .unwrap();
+    fs::write(&path, &original).unwrap();
+
+    localconfig::write_launch_options(&path, APP_ID, "PROTON_LOG=1 %command%").unwrap();
+    localconfig::write_launch_options(&path, 440, "-novid").unwrap();
+    localconfig::write_launch_options(&path, 620, "-dev").unwrap();
+    assert!(path.with_file_name("localconfig.vdf.bak").is_file());
+
+    let local_config = UserLocalConfig::load(&path).unwrap();
+    let launch_options = |app_id| local_config.app(app_id).unwrap().launch_options.as_deref();
+    assert_eq!(launch_options(APP_ID), Some("PROTON_LOG=1 %command%"));
+    assert_eq!(launch_options(440), Some("-novid"));
+    assert_eq!(launch_options(620), Some("-dev"));
+    assert_eq!(local_config.app(440).unwrap().playtime, Some(60));
+
+    // Everything else is left as it was
+    let edited = fs::read_to_string(&path).unwrap();
+    let expected = original
+        .replace("\"-novid -console\"", "\"PROTON_LOG=1 %command%\"")
+        .replace(
+            "\t\t\t\t\t\t\t\"last_sync_state\"\t\t\"pending\"\n\t\t\t\t\t\t}\n",
+            "\t\t\t\t\t\t\t\"last_sync_state\"\t\t\"pending\"\n\t\t\t\t\t\t}\n\t\t\t\t\t\t\"LaunchOptions\"\t\t\"-novid\"\n",
+        )
+        .replace(
+            "\t\t\t\t\t\t\t\"quota_files\"\t\t\"0\"\n\t\t\t\t\t\t}\n\t\t\t\t\t}\n",
+            "\t\t\t\t\t\t\t\"quota_files\"\t\t\"0\"\n\t\t\t\t\t\t}\n\t\t\t\t\t}\n\t\t\t\t\t\"620\"\n\t\t\t\t\t{\n\t\t\t\t\t\t\"LaunchOptions\"\t\t\"-dev\"\n\t\t\t\t\t}\n",
+        );
+    assert_eq!(edited, expected);
+
+    fs::write(&path, "\"UserLocalConfigStore\" { \"Software\" \"oops\" }").unwrap();
+    assert!(matches!(
+        localconfig::write_launch_options(&path, APP_ID, "-novid"),
+        Err(Error::UnsupportedLayout { .. })
+    ));
+    fs::write(&path, "\"UserLocalConfigStore\" {").unwrap();
+    assert!(matches!(
+        localconfig::write_launch_options(&path, APP_ID, "-novid"),
+        Err(Error::Parse { .. })
+    ));
+}
+
+#[test]
+fn compat_tool_mapping() {
+    let mut steamdir = SteamDir::from_dir(sample_steam_dir()).unwrap();
+    let mapping = steamdir.compat_tool_mapping().unwrap();
+
+    let gmod = steamdir
+        .app(&APP_ID)
+        .unwrap()
+        .compat_tool(&mapping)
+        .unwrap();
+    assert_eq!(gmod.name, "proton_8");
+    assert_eq!(gmod.config, "noesync,nofsync");
+    assert_eq!(gmod.priority, Some(250));
+
+    // A cleared mapping falls back to the default, just like an app without one
+    let default = mapping.default.as_ref().unwrap();
+    assert_eq!(default.name, "proton_experimental");
+    assert_eq!(default.priority, Some(75));
+    assert_eq!(mapping.get(440), Some(default));
+    assert_eq!(mapping.get(620), Some(default));
+    assert_eq!(mapping.apps.len(), 2);
+
+    let shortcut = steamdir
+        .shortcuts()
+        .iter()
+        .find(|shortcut| shortcut.appid == 2786274309)
+        .unwrap();
+    assert_eq!(
+        shortcut.compat_tool(&mapping).unwrap().name,
+        "GE-Proton8-25"
+    );
+
+    let dir = TempDir::new("compat-tool-mapping");
+    for subdir in &["steamapps", "config", "userdata"] {
+        fs::create_dir(dir.join(subdir)).unwrap();
+    }
+    let mapping = SteamDir::from_dir(&dir)
+        .unwrap()
+        .compat_tool_mapping()
+        .unwrap();
+    assert_eq!(mapping.get(APP_ID), None);
+}
+
+#[test]
+fn compat_data_path() {
+    let mut steamdir = SteamDir::from_dir(sample_steam_dir()).unwrap();
+    let libraryfolders = steamdir.libraryfolders().clone();
+    let compatdata = sample_steam_dir().join("steamapps/compatdata");
+
+    let gmod = steamdir
+        .app(&APP_ID)
+        .unwrap()
+        .compat_data_path(&libraryfolders)
+        .unwrap();
+    assert_eq!(gmod.path, compatdata.join("4000"));
+    assert_eq!(gmod.pfx, compatdata.join("4000/pfx"));
+    assert_eq!(gmod.version.as_deref(), Some("8.0-103"));
+    assert_eq!(gmod.config_info.as_deref(), Some("1695315413 proton-8.0-4"));
+
+    let shortcut = steamdir
+        .shortcuts()
+        .iter()
+        .find(|shortcut| shortcut.appid == 2786274309)
+        .unwrap()
+        .compat_data_path(&libraryfolders)
+        .unwrap();
+    assert_eq!(shortcut.path, compatdata.join("2786274309"));
+    assert_eq!(shortcut.version.as_deref(), Some("GE-Proton8-25"));
+    assert_eq!(shortcut.config_info, None);
+    assert_eq!(steamdir.compat_data(APP_ID), Some(gmod));
+    assert_eq!(steamdir.compat_data(2786274309), Some(shortcut));
+    assert_eq!(steamdir.compat_data(440), None);
+
+    // The prefix can be in a different library than the app
+    let root = TempDir::new("compat_data_path");
+    let library = root.join("library");
+    for dir in &["config", "userdata", "steamapps/compatdata/90"] {
+        fs::create_dir_all(root.join(dir)).unwrap();
+    }
+    fs::create_dir_all(library.join("steamapps/common/Game")).unwrap();
+    write_manifest(&library.join("steamapps"), 90, "Game");
+    fs::write(
+        root.join("steamapps/libraryfolders.vdf"),
+        format!(
+            "\"libraryfolders\"\n{{\n\t\"0\"\n\t{{\n\t\t\"path\"\t\t\"{}\"\n\t}}\n\t\"1\"\n\t{{\n\t\t\"path\"\t\t\"{}\"\n\t}}\n}}\n",
+            vdf_path(&root),
+            vdf_path(&library)
+        ),
+    )
+    .unwrap();
+
+    let mut steamdir = SteamDir::from_dir(&root).unwrap();
+    let libraryfolders = steamdir.libraryfolders().clone();
+    let app = steamdir.app(&90).unwrap();
+    let compat_data = app.compat_data_path(&libraryfolders).unwrap();
+    assert_eq!(compat_data.path, root.join("steamapps/compatdata/90"));
+    assert_eq!(compat_data.version, None);
+
+    fs::create_dir_all(library.join("steamapps/compatdata/90")).unwrap();
+    let compat_data = app.compat_data_path(&libraryfolders).unwrap();
+    assert_eq!(compat_data.path, library.join("steamapps/compatdata/90"));
+    assert_eq!(steamdir.compat_data(90), Some(compat_data));
+}
+
+#[test]
+fn compat_tool_names() {
+    let cases = [
+        ("Proton 3.7", Some("proton_37")),
+        ("Proton 5.13", Some("proton_513")),
+        ("Proton 10.0", Some("proton_10")),
+        ("Proton - Experimental", Some("proton_experimental")),
+        ("Proton Hotfix", Some("proton_hotfix")),
+        (
+            "SteamLinuxRuntime_soldier",
+            Some("steamlinuxruntime_soldier"),
+        ),
+        ("Proton EasyAntiCheat Runtime", None),
+        ("GarrysMod", None),
+    ];
+    for (install_dir, name) in cases.iter() {
+        assert_eq!(
+            compattool::name_from_install_dir(install_dir).as_deref(),
+            *name,
+            "{}",
+            install_dir
+        );
+    }
+}
+
+#[test]
+fn compat_tools() {
+    let steam_dir = sample_steam_dir();
+    let mut steamdir = SteamDir::from_dir(&steam_dir).unwrap();
+
+    // Tools installed on the running system don't belong to an installation from `from_dir`
+    let tools = steamdir.try_compat_tools().unwrap();
+    assert_eq!(tools, steamdir.compat_tools());
+    assert_eq!(
+        compattool::custom_tool_dirs(&steam_dir, false),
+        vec![steam_dir.join("compatibilitytools.d")]
+    );
+
+    let names: Vec<_> = tools.iter().map(|tool| tool.name.as_deref()).collect();
+    assert_eq!(
+        names,
+        vec![
+            Some("steamlinuxruntime_sniper"),
+            Some("proton_8"),
+            Some("GE-Proton8-25")
+        ]
+    );
+
+    let runtime = &tools[0];
+    assert_eq!(
+        runtime.display_name.as_deref(),
+        Some("Steam Linux Runtime 3.0 (sniper)")
+    );
+    assert_eq!(runtime.source, CompatToolSource::App(1628350));
+    assert_eq!(
+        runtime.commandline.as_deref(),
+        Some("/_v2-entry-point --verb=%verb% --")
+    );
+
+    let proton = &tools[1];
+    assert_eq!(proton.display_name.as_deref(), Some("Proton 8.0"));
+    assert_eq!(proton.path, steam_dir.join("steamapps/common/Proton 8.0"));
+    assert_eq!(proton.commandline.as_deref(), Some("/proton %verb%"));
+
+    let custom = &tools[2];
+    assert_eq!(custom.display_name.as_deref(), Some("GE-Proton8-25"));
+    assert_eq!(custom.source, CompatToolSource::Custom);
+    assert_eq!(
+        custom.path,
+        steam_dir.join("compatibilitytools.d/GE-Proton8-25")
+    );
+    assert_eq!(custom.commandline.as_deref(), Some("/proton %verb%"));
+
+    // A broken tool is skipped, unless errors are asked for
+    let dir = TempDir::new("compat_tools");
+    let dirs = [dir.to_path_buf()];
+    fs::create_dir_all(dir.join("broken")).unwrap();
+    fs::write(
+        dir.join("broken/compatibilitytool.vdf"),
+        "\"compat_tools\" {",
+    )
+    .unwrap();
+    assert!(compattool::discover_custom(&dirs, false)
+        .unwrap()
+        .is_empty());
+    assert!(matches!(
+        compattool::discover_custom(&dirs, true),
+        Err(Error::Parse { .. })
+    ));
+
+    // So is a folder that can't be read, which root can read anyway
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        let locked = [dir.join("locked")];
+        fs::create_dir(&locked[0]).unwrap();
+        fs::set_permissions(&locked[0], fs::Permissions::from_mode(0o000)).unwrap();
+        if fs::read_dir(&locked[0]).is_err() {
+            assert!(compattool::discover_custom(&locked, false)
+                .unwrap()
+                .is_empty());
+            assert!(matches!(
+                compattool::discover_custom(&locked, true),
+                Err(Error::Io { .. })
+            ));
+        }
+        fs::set_permissions(&locked[0], fs::Permissions::from_mode(0o755)).unwrap();
+    }
+}
+
+#[test]
+fn launch_chain() {
+    let mut steamdir = SteamDir::from_dir(sample_steam_dir()).unwrap();
+    let steamapps = sample_steam_dir().join("steamapps/common");
+
+    let chain = steamdir.launch_chain(APP_ID).unwrap();
+    let paths: Vec<_> = chain.iter().map(|tool| tool.path.clone()).collect();
+    assert_eq!(
+        paths,
+        vec![
+            steamapps.join("Proton 8.0"),
+            steamapps.join("SteamLinuxRuntime_sniper")
+        ]
+    );
+    assert_eq!(chain[0].require_tool_appid, Some(1628350));
+    assert_eq!(chain[1].require_tool_appid, None);
+
+    let chain = steamdir.launch_chain(2786274309).unwrap();
+    assert_eq!(chain[0].name.as_deref(), Some("GE-Proton8-25"));
+    assert_eq!(chain[1].source, CompatToolSource::App(1628350));
+
+    // The default tool isn't used, and neither is a cleared one
+    assert_eq!(steamdir.launch_chain(620).unwrap(), vec![]);
+    assert_eq!(steamdir.launch_chain(440).unwrap(), vec![]);
+
+    let mut tools = steamdir.compat_tools();
+    tools.retain(|tool| tool.source != CompatToolSource::Custom);
+    let mapping = steamdir.compat_tool_mapping().unwrap();
+    match compattool::resolve_chain(2786274309, &mapping, &tools) {
+        Err(Error::MissingCompatTool { app_id, tool }) => {
+            assert_eq!(app_id, 2786274309);
+            assert_eq!(tool, "GE-Proton8-25");
+        }
+        other => panic!("{:?}", other),
+    }
+
+    let mut tools = steamdir.compat_tools();
+    tools.retain(|tool| tool.source != CompatToolSource::App(1628350));
+    match compattool::resolve_chain(APP_ID, &mapping, &tools) {
+        Err(Error::MissingCompatTool { tool, .. }) => assert_eq!(tool, "app 1628350"),
+        other => panic!("{:?}", other),
+    }
+
+    let mut tools = steamdir.compat_tools();
+    for tool in &mut tools {
+        if tool.source == CompatToolSource::App(1628350) {
+            tool.require_tool_appid = Some(2348590);
+        }
+    }
+    assert!(matches!(
+        compattool::resolve_chain(APP_ID, &mapping, &tools),
+        Err(Error::UnsupportedLayout { .. })
+    ));
+
+    assert!(
+        compattool::resolve_chain(APP_ID, &CompatToolMapping::default(), &tools)
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[test]
+fn proton_prefix() {
+    let mut steamdir = SteamDir::from_dir(sample_steam_dir()).unwrap();
+    let libraryfolders = steamdir.libraryfolders().clone();
+    let prefix = steamdir
+        .app(&APP_ID)
+        .unwrap()
+        .compat_data_path(&libraryfolders)
+        .unwrap()
+        .prefix();
+    let settings = prefix
+        .known_folder(KnownFolder::Documents)
+        .join("settings.ini");
+    assert_eq!(
+        settings,
+        sample_steam_dir()
+            .join("steamapps/compatdata/4000/pfx/drive_c/users/steamuser/Documents/settings.ini")
+    );
+    assert!(settings.is_file());
+
+    // Without `dosdevices` only `C:` is known
+    assert_eq!(
+        prefix.to_unix("C:\\users\\steamuser\\Documents\\Settings.INI"),
+        Some(settings.clone())
+    );
+    assert_eq!(prefix.to_unix("Z:\\etc"), None);
+    assert_eq!(
+        prefix.to_windows(&settings).as_deref(),
+        Some("C:\\users\\steamuser\\Documents\\settings.ini")
+    );
+    assert_eq!(prefix.to_windows(&sample_steam_dir()), None);
+}
+
+#[test]
+fn prefix_registry() {
+    let mut steamdir = SteamDir::from_dir(sample_steam_dir()).unwrap();
+    let libraryfolders = steamdir.libraryfolders().clone();
+    let prefix = steamdir
+        .app(&APP_ID)
+        .unwrap()
+        .compat_data_path(&libraryfolders)
+        .unwrap()
+        .prefix();
+
+    let system = prefix.registry(RegistryHive::System).unwrap();
+    assert_eq!(system.relative_to().as_deref(), Some("\\Machine"));
+    assert_eq!(system.arch(), Some("win64"));
+    assert_eq!(system.keys().len(), 3);
+
+    let gmod = system
+        .key("software\\wow6432node\\facepunch\\garry's mod")
+        .unwrap();
+    assert_eq!(gmod.name(), "Software\\Wow6432Node\\Facepunch\\Garry's Mod");
+    assert_eq!(gmod.modified(), Some(1699999000));
+    assert_eq!(gmod.time(), Some(0x1da1b2c3d4e5f60));
+    assert_eq!(gmod.value("").unwrap().as_str(), Some("Garry's Mod"));
+    let install_dir = gmod.value("InstallDir").unwrap().as_str().unwrap();
+    assert_eq!(
+        install_dir,
+        "C:\\Program Files (x86)\\Steam\\steamapps\\common\\GarrysMod"
+    );
+    assert_eq!(gmod.value("Owner").unwrap().as_str(), Some("Café \"Bob\""));
+    assert_eq!(
+        gmod.value("Path"),
+        Some(&RegistryValue::ExpandString(
+            "%ProgramFiles%\\GarrysMod".to_string()
+        ))
+    );
+    assert_eq!(gmod.value("Version").unwrap().as_dword(), Some(0xa1b2));
+    assert_eq!(
+        gmod.value("Signature").unwrap().as_bytes(),
+        Some(&(0..0x1b).collect::<Vec<u8>>()[..])
+    );
+    assert_eq!(
+        gmod.value("Mods"),
+        Some(&RegistryValue::Hex {
+            kind: 7,
+            data: vec![0x61, 0, 0, 0, 0x62, 0, 0, 0, 0, 0]
+        })
+    );
+    // Kept in the file, but not understood
+    assert_eq!(gmod.value("Broken"), None);
+    assert_eq!(
+        system.value(
+            "System\\CurrentControlSet\\Control\\Session Manager\\Environment",
+            "windir"
+        ),
+        Some(&RegistryValue::ExpandString("%SystemRoot%".to_string()))
+    );
+
+    let user = prefix.registry(RegistryHive::User).unwrap();
+    assert_eq!(
+        user.relative_to().as_deref(),
+        Some("\\User\\S-1-5-21-0-0-0-1000")
+    );
+    assert_eq!(
+        user.value("Software\\Wine\\Direct3D", "renderer"),
+        Some(&RegistryValue::String("vulkan".to_string()))
+    );
+    assert!(matches!(
+        prefix.registry(RegistryHive::UserDefault),
+        Err(Error::Io { .. })
+    ));
+}
@@ -0,0 +1,41 @@
+//! Safely replacing files that Steam reads
+
+use std::{
+    ffi::OsString,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use crate::error::{Error, Result};
+
+/// Replaces the file at `path` with `contents`, keeping the previous version next to it as
+/// `<name>.bak`
+///
+/// The contents are written to `<name>.tmp` first and then renamed over the original, so Steam
+/// never sees a partially written file
+pub(crate) fn replace_with_backup(path: &Path, contents: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|err| Error::io(err, parent))?;
+    }
+
+    let temp_path = with_suffix(path, ".tmp");
+    let write_temp = || -> std::io::Result<()> {
+        let mut temp = fs::File::create(&temp_path)?;
+        temp.write_all(contents)?;
+        temp.sync_all()
+    };
+    write_temp().map_err(|err| Error::io(err, &temp_path))?;
+
+    if path.exists() {
+        let backup_path = with_suffix(path, ".bak");
+        fs::copy(path, &backup_path).map_err(|err| Error::io(err, &backup_path))?;
+    }
+    fs::rename(&temp_path, path).map_err(|err| Error::io(err, path))
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().map(OsString::from).unwrap_or_default();
+    name.push(suffix);
+    path.with_file_name(name)
+}